@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::{sync::Arc, mem};
 
-use crate::{OrNode, ClassificationNode, Hinge, NamedNode, AlwaysTrueNode, OneTokenNode, ListNode, OptionalTokenNode, HingeConsumer, HelpNode, KeyWrapNode, MandatoryItemsNode};
+use crate::{TemplateRenderer, Result, HelpRenderer, ColorChoice, Theme, DeprecatedNode, SubcommandNode, HelpCommandNode, Fallback, FallbackNode, ChoiceNode, OrNode, ClassificationNode, Hinge, NamedNode, AlwaysTrueNode, OneTokenNode, ListNode, OptionalTokenNode, HingeConsumer, HelpNode, KeyWrapNode, MandatoryItemsNode, FailNode, ChainNode, ContextNode, DisplayNode, PrefixNode, HelpScopeNode, ActionNode, ActionFn, HiddenNode, HingeHelp, HingeErrorKind};
 
 #[derive(Debug, Clone)]
 pub enum FlagName {
@@ -53,7 +53,11 @@ pub trait RequirableField {
   fn required_id(&self) -> &str;
 }
 
-pub struct RequirableItem(String);
+pub trait DescribableField {
+  fn described_id(&self) -> &str;
+}
+
+pub struct RequirableItem(String, bool);
 
 impl RequirableField for RequirableItem {
   fn required_id(&self) -> &str {
@@ -61,45 +65,141 @@ impl RequirableField for RequirableItem {
   }
 }
 
+impl DescribableField for RequirableItem {
+  fn described_id(&self) -> &str {
+    &self.0
+  }
+}
+
+pub struct DescribableItem(String);
+
+impl DescribableField for DescribableItem {
+  fn described_id(&self) -> &str {
+    &self.0
+  }
+}
+
 pub struct Default;
 
+#[derive(Debug, Clone)]
+enum SubcommandFallback {
+  Core,
+  Required,
+  Default(KeyWrapNode)
+}
+
 #[derive(Debug, Clone)]
 pub struct HingeBuilder<T> {
-  subcommands: OrNode,
+  subcommands: Vec<HelpNode>,
+  subcommand_names: Vec<String>,
+  fallback: SubcommandFallback,
+  chain: Option<Option<String>>,
   node: ClassificationNode,
-  mandatory: HashSet<String>,
+  mandatory: Vec<String>,
+  fallbacks: Vec<(String, Fallback)>,
+  collect_errors: bool,
+  help_flag: Option<FlagName>,
+  program: Option<String>,
+  version: Option<String>,
+  version_flag: Option<FlagName>,
+  help_json_flag: Option<FlagName>,
+  color_flag: Option<FlagName>,
+  help_width: Option<usize>,
+  about: Option<String>,
+  long_about: Option<String>,
+  after_help: Option<String>,
+  heading: Option<String>,
+  sorted: bool,
+  color: Option<ColorChoice>,
+  theme: Option<Theme>,
+  renderer: Option<Arc<dyn HelpRenderer>>,
+  template: Option<String>,
   state: T
 }
 
-impl<T> Into<Hinge> for HingeBuilder<T> {
-  fn into(self) -> Hinge {
-    self.build()
+impl<T> From<HingeBuilder<T>> for Hinge {
+  fn from(value: HingeBuilder<T>) -> Self {
+    value.build()
   }
 }
 
 impl HingeBuilder<Default> {
   pub fn new() -> Self {
     HingeBuilder {
-      subcommands: OrNode::new(),
+      subcommands: Vec::new(),
+      subcommand_names: Vec::new(),
+      fallback: SubcommandFallback::Core,
+      chain: None,
       node: ClassificationNode::new(),
-      mandatory: HashSet::new(),
+      mandatory: Vec::new(),
+      fallbacks: Vec::new(),
+      collect_errors: false,
+      help_flag: Some(('h', "help").into()),
+      program: None,
+      version: None,
+      version_flag: Some(('V', "version").into()),
+      help_json_flag: None,
+      color_flag: None,
+      help_width: None,
+      about: None,
+      long_about: None,
+      after_help: None,
+      heading: None,
+      sorted: false,
+      color: None,
+      theme: None,
+      renderer: None,
+      template: None,
       state: Default
     }
   }
 }
 
+impl std::default::Default for HingeBuilder<Default> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl<T> HingeBuilder<T> {
   fn fork<K>(self, state: K) -> HingeBuilder<K> {
-    HingeBuilder { subcommands: self.subcommands, node: self.node, mandatory: self.mandatory, state }
+    HingeBuilder {
+      subcommands: self.subcommands,
+      subcommand_names: self.subcommand_names,
+      fallback: self.fallback,
+      chain: self.chain,
+      node: self.node,
+      mandatory: self.mandatory,
+      fallbacks: self.fallbacks,
+      collect_errors: self.collect_errors,
+      help_flag: self.help_flag,
+      program: self.program,
+      version: self.version,
+      version_flag: self.version_flag,
+      help_json_flag: self.help_json_flag,
+      color_flag: self.color_flag,
+      help_width: self.help_width,
+      about: self.about,
+      long_about: self.long_about,
+      after_help: self.after_help,
+      heading: self.heading,
+      sorted: self.sorted,
+      color: self.color,
+      theme: self.theme,
+      renderer: self.renderer,
+      template: self.template,
+      state
+    }
   }
 
   pub fn bool(
     self,
     id: impl AsRef<str>,
     name: impl Into<FlagName>
-  ) -> HingeBuilder<Default> {
+  ) -> HingeBuilder<DescribableItem> {
     let names: FlagName = name.into();
     self.include(&id, NamedNode::new(names.collect(), AlwaysTrueNode), true)
+      .fork(DescribableItem(id.as_ref().to_string()))
   }
   
   pub fn item(
@@ -109,7 +209,7 @@ impl<T> HingeBuilder<T> {
   ) -> HingeBuilder<RequirableItem> {
     let names: FlagName = name.into();
     self.include(&id, NamedNode::new(names.collect(), OneTokenNode), true)
-      .fork(RequirableItem(id.as_ref().to_string()))
+      .fork(RequirableItem(id.as_ref().to_string(), false))
   }
 
   pub fn list(
@@ -120,14 +220,34 @@ impl<T> HingeBuilder<T> {
   ) -> HingeBuilder<RequirableItem> {
     let names: FlagName = name.into();
     self.include(&id, NamedNode::new(names.collect(), ListNode::new(count)), true)
-      .fork(RequirableItem(id.as_ref().to_string()))
+      .fork(RequirableItem(id.as_ref().to_string(), true))
   }
 
-  pub fn catch_tail(
+  pub fn action(
     self,
+    id: impl AsRef<str>,
+    name: impl Into<FlagName>,
+    action: impl ActionFn + 'static
+  ) -> HingeBuilder<DescribableItem> {
+    let names: FlagName = name.into();
+    self.include(&id, NamedNode::new(names.collect(), ActionNode::new(&id, action)), true)
+      .fork(DescribableItem(id.as_ref().to_string()))
+  }
+
+  pub fn catch_tail(
+    mut self,
     id: impl AsRef<str>
   ) -> HingeBuilder<Default> {
-    self.include(id, NamedNode::new(vec!["--"], ListNode::new(None)), true)
+    self.node.set_tail(id);
+    self.fork(Default)
+  }
+
+  pub fn stop_at_operand(
+    mut self,
+    id: impl AsRef<str>
+  ) -> HingeBuilder<Default> {
+    self.node.set_stop_at_operand(id);
+    self.fork(Default)
   }
 
   pub fn arg(
@@ -135,7 +255,7 @@ impl<T> HingeBuilder<T> {
     id: impl AsRef<str>,
   ) -> HingeBuilder<RequirableItem> {
     self.include(&id, OptionalTokenNode, false)
-      .fork(RequirableItem(id.as_ref().to_string()))
+      .fork(RequirableItem(id.as_ref().to_string(), false))
   }
 
   pub fn include(
@@ -145,6 +265,9 @@ impl<T> HingeBuilder<T> {
     prioritary: bool
   ) -> HingeBuilder<Default> {
     self.node.put(&id, node, prioritary);
+    if let Some(heading) = self.heading.clone() {
+      self.node.map_entry(&id, |entry| HelpNode::new(entry).heading(heading));
+    }
     self.fork(Default)
   }
 
@@ -155,27 +278,496 @@ impl<T> HingeBuilder<T> {
     hinge: impl Into<Hinge>
   ) -> Self {
     let hinge: Hinge = hinge.into();
-    self.subcommands.put(HelpNode::new(
-      KeyWrapNode::new(id.as_ref().to_string(), NamedNode::new(vec![name], hinge.extract()))
+    let consumer = SubcommandNode::new(&name, hinge.extract());
+    let consumer = ContextNode::new(format!("in subcommand `{}`", name.as_ref()), consumer);
+    self.subcommand_names.push(name.as_ref().to_string());
+    self.subcommands.push(HelpNode::new(
+      KeyWrapNode::new(id, NamedNode::new(vec![name], consumer))
     ).tabulate());
     self
   }
 
+  pub fn default_subcommand(
+    mut self,
+    id: impl AsRef<str>,
+    name: impl AsRef<str>,
+    hinge: impl Into<Hinge>
+  ) -> Self {
+    let hinge: Hinge = hinge.into();
+    let consumer = SubcommandNode::new(&name, hinge.extract());
+    let consumer = Arc::new(ContextNode::new(format!("in subcommand `{}`", name.as_ref()), consumer));
+    self.subcommand_names.push(name.as_ref().to_string());
+    self.subcommands.push(HelpNode::new(
+      KeyWrapNode::new(&id, NamedNode::new(vec![name], consumer.clone()))
+    ).tabulate().annotation("default"));
+    self.fallback = SubcommandFallback::Default(KeyWrapNode::new(id, consumer));
+    self
+  }
+
+  pub fn require_subcommand(mut self) -> Self {
+    if !matches!(self.fallback, SubcommandFallback::Default(_)) {
+      self.fallback = SubcommandFallback::Required;
+    }
+    self
+  }
+
+  pub fn abbreviate_long_options(mut self) -> Self {
+    self.node.set_abbreviate(true);
+    self
+  }
+
+  pub fn collect_errors(mut self) -> Self {
+    self.node.set_collect_errors(true);
+    self.collect_errors = true;
+    self
+  }
+
+  pub fn chain_subcommands(mut self) -> Self {
+    self.chain = Some(None);
+    self
+  }
+
+  pub fn chain_separator(mut self, separator: impl AsRef<str>) -> Self {
+    self.chain = Some(Some(separator.as_ref().to_string()));
+    self
+  }
+
+  pub fn help_flag(mut self, name: impl Into<FlagName>) -> Self {
+    self.help_flag = Some(name.into());
+    self
+  }
+
+  pub fn disable_help_flag(mut self) -> Self {
+    self.help_flag = None;
+    self
+  }
+
+  pub fn program(mut self, program: impl AsRef<str>) -> Self {
+    self.program = Some(program.as_ref().to_string());
+    self
+  }
+
+  pub fn version(mut self, version: impl AsRef<str>) -> Self {
+    self.version = Some(version.as_ref().to_string());
+    self
+  }
+
+  pub fn version_flag(mut self, name: impl Into<FlagName>) -> Self {
+    self.version_flag = Some(name.into());
+    self
+  }
+
+  pub fn disable_version_flag(mut self) -> Self {
+    self.version_flag = None;
+    self
+  }
+
+  pub fn help_json_flag(mut self, name: impl Into<FlagName>) -> Self {
+    self.help_json_flag = Some(name.into());
+    self
+  }
+
+  pub fn color_flag(mut self, name: impl Into<FlagName>) -> Self {
+    self.color_flag = Some(name.into());
+    self
+  }
+
+  pub fn help_width(mut self, width: usize) -> Self {
+    self.help_width = Some(width);
+    self
+  }
+
+  pub fn about(mut self, about: impl AsRef<str>) -> Self {
+    self.about = Some(about.as_ref().to_string());
+    self
+  }
+
+  pub fn long_about(mut self, about: impl AsRef<str>) -> Self {
+    self.long_about = Some(about.as_ref().to_string());
+    self
+  }
+
+  pub fn after_help(mut self, after_help: impl AsRef<str>) -> Self {
+    self.after_help = Some(after_help.as_ref().to_string());
+    self
+  }
+
+  pub fn heading(mut self, heading: impl AsRef<str>) -> Self {
+    self.heading = Some(heading.as_ref().to_string());
+    self
+  }
+
+  pub fn sort_alphabetically(mut self) -> Self {
+    self.sorted = true;
+    self
+  }
+
+  pub fn color(mut self, color: ColorChoice) -> Self {
+    self.color = Some(color);
+    self
+  }
+
+  pub fn theme(mut self, theme: Theme) -> Self {
+    self.theme = Some(theme);
+    self
+  }
+
+  pub fn help_renderer(mut self, renderer: impl HelpRenderer + 'static) -> Self {
+    self.renderer = Some(Arc::new(renderer));
+    self
+  }
+
+  pub fn help_template(mut self, template: impl AsRef<str>) -> Self {
+    self.template = Some(template.as_ref().to_string());
+    self
+  }
+
+  fn display_requests(&self) -> Vec<(&'static str, NamedNode)> {
+    let version_flag = self.version.as_ref().and(self.version_flag.as_ref());
+    let taken = self.node.known_names();
+    [
+      ("help", self.help_flag.as_ref(), HingeErrorKind::DisplayHelp),
+      ("version", version_flag, HingeErrorKind::DisplayVersion),
+      ("help-json", self.help_json_flag.as_ref(), HingeErrorKind::DisplayHelpJson)
+    ]
+      .into_iter()
+      .filter_map(|(id, name, kind)| {
+        let names: Vec<String> = name?.collect().into_iter().filter(|x| !taken.contains(x)).collect();
+        (!names.is_empty()).then(|| (id, NamedNode::new(names, DisplayNode::new(kind))))
+      })
+      .collect()
+  }
+
+  fn color_entry(&self) -> Option<(Vec<String>, HelpNode)> {
+    let taken = self.node.known_names();
+    let names: Vec<String> = self.color_flag.as_ref()?.collect().into_iter().filter(|x| !taken.contains(x)).collect();
+    if names.is_empty() {
+      return None
+    }
+    let choice = ChoiceNode::new(NamedNode::new(names.clone(), OneTokenNode), ["auto", "always", "never"].map(String::from).to_vec());
+    let entry = HelpNode::new_with(choice, |help: &mut HingeHelp| help.set_value_name("WHEN")).description("When to use colors");
+    Some((names, entry))
+  }
+
+  fn display_entry(id: &str, request: NamedNode) -> Box<dyn HingeConsumer> {
+    match id {
+      "help" => Box::new(HelpNode::new(request).description("Print help")),
+      "version" => Box::new(HelpNode::new(request).description("Print version")),
+      _ => Box::new(HiddenNode::new(request))
+    }
+  }
+
   pub fn build(
     self
   ) -> Hinge {
-    let core = MandatoryItemsNode::new(self.node, self.mandatory.into_iter().collect());
-    if self.subcommands.len() > 0 {
-      self.subcommands.or(core).into()
-    } else {
-      core.into()
+    self.try_build().unwrap_or_else(|error| panic!("invalid hinge definition: {}", error))
+  }
+
+  pub fn try_build(
+    mut self
+  ) -> Result<Hinge> {
+    if let Some(template) = self.template.take() {
+      self.renderer = Some(Arc::new(TemplateRenderer::new(template)?));
+    }
+    let requests = self.display_requests();
+    let color = self.color_entry();
+    self.heading = None;
+    for (id, request) in requests {
+      self.node.put_builtin(id, Self::display_entry(id, request));
+    }
+    let color_flag = match color {
+      Some((names, entry)) => {
+        self.node.put_builtin("color", entry);
+        names
+      },
+      None => Vec::new()
+    };
+    let prefixed = !matches!(self.fallback, SubcommandFallback::Core);
+    if prefixed && !self.subcommands.is_empty() && self.node.has_positionals() {
+      return Err("positional arguments cannot be combined with required or default subcommands".to_string().into())
+    }
+    let node = FallbackNode::new(mem::take(&mut self.node), mem::take(&mut self.fallbacks));
+    if self.subcommands.is_empty() {
+      let core = self.mandatory(node);
+      return Ok(self.scope(core, color_flag))
+    }
+    let mut root = OrNode::new();
+    let mut subcommands = mem::take(&mut self.subcommands);
+    if self.help_flag.is_some() && !self.subcommand_names.iter().any(|x| x == "help") {
+      subcommands.push(HelpNode::new(NamedNode::new(vec!["help"], HelpCommandNode)).description("Print help for a subcommand"));
+    }
+    match self.chain.take() {
+      Some(separator) => root.put(ChainNode::new(subcommands.into_iter().fold(OrNode::new(), OrNode::or), separator)),
+      None => subcommands.into_iter().for_each(|subcommand| root.put(subcommand))
+    }
+    match mem::replace(&mut self.fallback, SubcommandFallback::Core) {
+      SubcommandFallback::Core => {
+        let core = self.mandatory(node);
+        root.put(core);
+        return Ok(self.scope(root, color_flag))
+      },
+      SubcommandFallback::Required => {
+        let message = format!("expecting a subcommand, available: {}", self.subcommand_names.join(", "));
+        root.put(HelpNode::new_with(FailNode::new(HingeErrorKind::MissingRequired, message), |help: &mut HingeHelp| {
+          help.set_alternative_name("<COMMAND>");
+          help.set_required(true);
+        }))
+      },
+      SubcommandFallback::Default(node) => root.put(HiddenNode::new(node))
     }
+    let root = self.mandatory(PrefixNode::new(node, root));
+    Ok(self.scope(root, color_flag))
+  }
+
+  fn mandatory(&mut self, node: impl HingeConsumer + 'static) -> MandatoryItemsNode {
+    let core = MandatoryItemsNode::new(node, mem::take(&mut self.mandatory));
+    match self.collect_errors {
+      true => core.collecting_errors(),
+      false => core
+    }
+  }
+
+  fn scope(&self, node: impl HingeConsumer + 'static, color_flag: Vec<String>) -> Hinge {
+    let mut scope = HelpScopeNode::new(node).color_flag(color_flag);
+    if let Some(name) = &self.help_json_flag {
+      scope = scope.help_json_flag(name.collect());
+    }
+    if let Some(width) = self.help_width {
+      scope = scope.width(width);
+    }
+    if let Some(program) = &self.program {
+      scope = scope.program(program);
+    }
+    if let Some(version) = &self.version {
+      scope = scope.version(version);
+    }
+    if let Some(about) = &self.about {
+      scope = scope.about(about);
+    }
+    if let Some(about) = &self.long_about {
+      scope = scope.long_about(about);
+    }
+    if let Some(after_help) = &self.after_help {
+      scope = scope.after_help(after_help);
+    }
+    if self.sorted {
+      scope = scope.sorted();
+    }
+    if let Some(color) = self.color {
+      scope = scope.color(color);
+    }
+    if let Some(theme) = self.theme {
+      scope = scope.theme(theme);
+    }
+    if let Some(renderer) = &self.renderer {
+      scope = scope.renderer(renderer.clone());
+    }
+    scope.into()
   }
 }
 
 impl<T : RequirableField> HingeBuilder<T> {
   pub fn require(mut self) -> Self {
-    self.mandatory.insert(self.state.required_id().to_string());
+    let id = self.state.required_id().to_string();
+    if !self.mandatory.contains(&id) {
+      self.mandatory.push(id);
+    }
+    self
+  }
+}
+
+impl<T : DescribableField> HingeBuilder<T> {
+  pub fn help(mut self, description: impl AsRef<str>) -> Self {
+    let description = description.as_ref().to_string();
+    self.node.map_entry(self.state.described_id(), |entry| HelpNode::new(entry).description(description));
+    self
+  }
+
+  pub fn hide(mut self) -> Self {
+    self.node.map_entry(self.state.described_id(), HiddenNode::new);
+    self
+  }
+
+  pub fn deprecated(mut self) -> Self {
+    self.node.map_entry(self.state.described_id(), DeprecatedNode::new);
+    self
+  }
+
+  pub fn deprecated_for(mut self, replacement: impl AsRef<str>) -> Self {
+    self.node.map_entry(self.state.described_id(), |entry| DeprecatedNode::new(entry).replacement(replacement));
+    self
+  }
+
+  pub fn conflicts_with(mut self, other: impl AsRef<str>) -> Self {
+    self.node.add_conflict(self.state.described_id(), other);
+    self
+  }
+
+  pub fn long_help(mut self, description: impl AsRef<str>) -> Self {
+    let description = description.as_ref().to_string();
+    self.node.map_entry(self.state.described_id(), |entry| HelpNode::new(entry).long_description(description));
+    self
+  }
+
+  pub fn value_name(mut self, value_name: impl AsRef<str>) -> Self {
+    let value_name = value_name.as_ref().to_string();
+    self.node.map_entry(self.state.described_id(), |entry| {
+      HelpNode::new_with(entry, move |help: &mut HingeHelp| help.set_value_name(&value_name))
+    });
     self
   }
-}
\ No newline at end of file
+}
+
+impl HingeBuilder<RequirableItem> {
+  fn fallback(&mut self) -> &mut Fallback {
+    let id = &self.state.0;
+    let position = match self.fallbacks.iter().position(|x| &x.0 == id) {
+      Some(position) => position,
+      None => {
+        self.fallbacks.push((id.clone(), Fallback::new(self.state.1)));
+        self.fallbacks.len() - 1
+      }
+    };
+    &mut self.fallbacks[position].1
+  }
+
+  pub fn default_value(mut self, default: impl AsRef<str>) -> Self {
+    let fallback = self.fallback();
+    *fallback = fallback.clone().default_value(default);
+    self
+  }
+
+  pub fn env(mut self, env: impl AsRef<str>) -> Self {
+    let fallback = self.fallback();
+    *fallback = fallback.clone().env(env);
+    self
+  }
+
+  pub fn possible_values(mut self, values: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+    let values: Vec<String> = values.into_iter().map(|x| x.as_ref().to_string()).collect();
+    self.node.map_entry(&self.state.0, |entry| ChoiceNode::new(entry, values));
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Hinge, HingeBuilder, HingeErrorKind};
+  use crate::testing::parse;
+
+  fn required() -> Hinge {
+    HingeBuilder::new()
+      .bool("verbose", 'v')
+      .item("cfg", "config").require()
+      .subcommand("run", "run", HingeBuilder::new().bool("dry", "dry"))
+      .require_subcommand()
+      .build()
+  }
+
+  fn defaulted() -> Hinge {
+    HingeBuilder::new()
+      .bool("verbose", 'v')
+      .subcommand("run", "run", HingeBuilder::new().bool("dry", "dry"))
+      .default_subcommand("show", "show", HingeBuilder::new().arg("name"))
+      .build()
+  }
+
+  #[test]
+  fn required_subcommand_keeps_root_options() {
+    let output = parse(&required(), &["-v", "--config", "x", "run", "--dry"]).unwrap();
+    assert!(output.get_item("verbose").unwrap().is_true());
+    assert!(output.get_item("cfg").unwrap().is_value(Some("x")));
+    assert!(output.get_item("run").unwrap().get_item("dry").unwrap().is_true());
+  }
+
+  #[test]
+  fn required_subcommand_validates_root_options() {
+    let error = parse(&required(), &["run"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::MissingRequired));
+    assert_eq!(error.parse_error().and_then(|x| x.id()), Some("cfg"));
+  }
+
+  #[test]
+  fn required_subcommand_is_still_required() {
+    let error = parse(&required(), &["--config", "x"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::MissingRequired));
+    assert!(error.to_string().contains("expecting a subcommand"));
+  }
+
+  #[test]
+  fn required_subcommand_help_lists_root_options() {
+    let help = required().help();
+    assert!(help.contains("-v"));
+    assert!(help.contains("--config"));
+    assert!(help.contains("run"));
+  }
+
+  #[test]
+  fn default_subcommand_keeps_root_options() {
+    let output = parse(&defaulted(), &["-v", "name"]).unwrap();
+    assert!(output.get_item("verbose").unwrap().is_true());
+    assert!(output.get_item("show").unwrap().get_item("name").unwrap().is_value(Some("name")));
+    let output = parse(&defaulted(), &["-v", "run"]).unwrap();
+    assert!(output.get_item("verbose").unwrap().is_true());
+    assert!(output.get_item("run").is_ok());
+  }
+
+  #[test]
+  fn positionals_conflict_with_required_subcommand() {
+    let result = HingeBuilder::new()
+      .arg("file")
+      .subcommand("run", "run", HingeBuilder::new())
+      .require_subcommand()
+      .try_build();
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn builtin_flags_yield_to_user_names() {
+    let hinge = HingeBuilder::new().bool("host", ('h', "host")).version("1.0").bool("verbose", 'V').build();
+    let help = hinge.help();
+    assert!(help.contains("-h, --host"));
+    assert!(help.contains("\n  --help "));
+    assert!(help.contains("\n  --version "));
+    assert!(parse(&hinge, &["-h"]).unwrap().get_item("host").unwrap().is_true());
+    assert_eq!(parse(&hinge, &["--help"]).unwrap_err().kind(), Some(HingeErrorKind::DisplayHelp));
+  }
+
+  #[test]
+  fn builtin_flags_survive_user_ids() {
+    let hinge = HingeBuilder::new().item("help", "topic").build();
+    assert_eq!(parse(&hinge, &["--topic", "x", "--help"]).unwrap_err().kind(), Some(HingeErrorKind::DisplayHelp));
+    assert!(parse(&hinge, &["--topic", "x"]).unwrap().get_item("help").unwrap().is_value(Some("x")));
+    let hinge = HingeBuilder::new().item("version", "pkg").version("1.0").build();
+    assert_eq!(parse(&hinge, &["-V"]).unwrap_err().kind(), Some(HingeErrorKind::DisplayVersion));
+    let hinge = HingeBuilder::new().item("color", "colour").color_flag("color").build();
+    let output = parse(&hinge, &["--color", "never", "--colour", "red"]).unwrap();
+    assert!(output.get_item("color").unwrap().is_value(Some("red")));
+    let error = parse(&hinge, &["--color", "red"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::InvalidValue));
+  }
+
+  #[test]
+  fn builtin_flags_stay_out_of_the_output() {
+    let hinge = HingeBuilder::new()
+      .version("1.0")
+      .color_flag("color")
+      .subcommand("run", "run", HingeBuilder::new())
+      .build();
+    let output = parse(&hinge, &["--color", "never"]).unwrap();
+    assert!(["help", "version", "color"].iter().all(|x| output.get_item(x).is_err()), "{:?}", output);
+    let output = parse(&hinge, &["run"]).unwrap();
+    assert!(output.get_item("run").unwrap().get_item("help").is_err(), "{:?}", output);
+  }
+
+  #[test]
+  fn core_options_are_an_alternative_to_subcommands() {
+    let hinge = HingeBuilder::new()
+      .bool("verbose", 'v')
+      .subcommand("run", "run", HingeBuilder::new().bool("dry", "dry"))
+      .build();
+    assert!(parse(&hinge, &["-v"]).unwrap().get_item("verbose").unwrap().is_true());
+    assert!(parse(&hinge, &["run", "--dry"]).unwrap().get_item("run").unwrap().get_item("dry").unwrap().is_true());
+    assert_eq!(parse(&hinge, &["-v", "run"]).unwrap_err().kind(), Some(HingeErrorKind::UnexpectedToken));
+  }
+}
@@ -1,5 +1,7 @@
 mod builder;
 mod product;
+mod outcome;
 
 pub use builder::*;
-pub use product::*;
\ No newline at end of file
+pub use product::*;
+pub use outcome::*;
\ No newline at end of file
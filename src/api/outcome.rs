@@ -0,0 +1,80 @@
+use std::{io, process};
+
+use crate::{HingeOutput, HingeError, HingeErrorKind};
+
+pub const EX_OK: i32 = 0;
+pub const EX_USAGE: i32 = 64;
+pub const EX_DATAERR: i32 = 65;
+pub const EX_SOFTWARE: i32 = 70;
+pub const EX_IOERR: i32 = 74;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+  Stdout,
+  Stderr
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+  Parsed(HingeOutput),
+  Exit {
+    code: i32,
+    stream: Stream,
+    message: String
+  }
+}
+
+impl Outcome {
+  pub fn is_parsed(&self) -> bool {
+    matches!(self, Self::Parsed(_))
+  }
+
+  pub fn exit_code(&self) -> Option<i32> {
+    match self {
+      Self::Parsed(_) => None,
+      Self::Exit { code, .. } => Some(*code)
+    }
+  }
+
+  pub fn message(&self) -> &str {
+    match self {
+      Self::Parsed(_) => "",
+      Self::Exit { message, .. } => message
+    }
+  }
+
+  pub fn or_exit(self) -> HingeOutput {
+    match self {
+      Self::Parsed(output) => output,
+      Self::Exit { code, .. } if self.message().is_empty() => process::exit(code),
+      Self::Exit { code, stream: Stream::Stdout, message } => {
+        println!("{}", message);
+        process::exit(code)
+      },
+      Self::Exit { code, stream: Stream::Stderr, message } => {
+        eprintln!("{}", message);
+        process::exit(code)
+      }
+    }
+  }
+}
+
+impl HingeError {
+  pub fn is_display_request(&self) -> bool {
+    matches!(self.kind(), Some(HingeErrorKind::DisplayHelp | HingeErrorKind::DisplayHelpJson | HingeErrorKind::DisplayVersion))
+  }
+
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      Self::Multiple { errors, .. } => errors.first().map_or(EX_USAGE, |x| x.exit_code()),
+      Self::Context { source, .. } => source.exit_code(),
+      Self::Wrapper(wrapped) if wrapped.is::<io::Error>() => EX_IOERR,
+      Self::Wrapper(_) | Self::String(_) => EX_SOFTWARE,
+      Self::Parse(error) => match error.kind() {
+        HingeErrorKind::DisplayHelp | HingeErrorKind::DisplayHelpJson | HingeErrorKind::DisplayVersion | HingeErrorKind::ShortCircuit => EX_OK,
+        HingeErrorKind::InvalidValue | HingeErrorKind::ValidationFailed => EX_DATAERR,
+        _ => EX_USAGE
+      }
+    }
+  }
+}
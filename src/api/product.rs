@@ -1,4 +1,4 @@
-use crate::{HingeConsumer, Token, HingeOutput, HingeHelp, Result};
+use crate::{JsonRenderer, ColorChoice, ParseContext, Diagnostic, HingeConsumer, Token, HingeOutput, HingeHelp, Result, HingeError, HingeErrorKind, suggest, Outcome, Stream, EX_OK};
 
 #[derive(Debug)]
 pub struct Hinge(Box<dyn HingeConsumer>);
@@ -8,30 +8,149 @@ impl Hinge {
     Hinge(Box::new(consumer))
   }
 
-  pub fn apply_tokens(&self, tokens: impl Iterator<Item = Token> + 'static) -> Result<HingeOutput> {
-    let mut tokens: Box<dyn Iterator<Item = Token>> = Box::new(tokens);
-    let result = self.0.consume(&mut tokens)?;
+  pub fn apply_tokens(&self, tokens: impl Iterator<Item = String> + 'static) -> Result<HingeOutput> {
+    self.apply_tokens_with_diagnostics(tokens).0
+  }
+
+  pub fn apply_tokens_with_diagnostics(&self, tokens: impl Iterator<Item = String> + 'static) -> (Result<HingeOutput>, Vec<Diagnostic>) {
+    let mut context = ParseContext::new();
+    let result = self.apply_tokens_checked(tokens, &mut context);
+    (result, context.into_diagnostics())
+  }
+
+  fn apply_tokens_checked(&self, tokens: impl Iterator<Item = String> + 'static, context: &mut ParseContext) -> Result<HingeOutput> {
+    match self.apply_tokens_unchecked(tokens, context) {
+      Ok(output) => Ok(output),
+      Err(error) => match error.errors().into_iter().find(|x| x.kind() == Some(HingeErrorKind::ShortCircuit)) {
+        Some(short_circuit) => {
+          let id = short_circuit.parse_error().and_then(|x| x.id()).unwrap_or_default();
+          Ok(HingeOutput::ShortCircuit(id.to_string()))
+        },
+        None => Err(self.suggest(error))
+      }
+    }
+  }
+
+  fn apply_tokens_unchecked(&self, tokens: impl Iterator<Item = String> + 'static, context: &mut ParseContext) -> Result<HingeOutput> {
+    let mut tokens: Box<dyn Iterator<Item = Token>> = Box::new(
+      tokens.enumerate().map(|(index, value)| Token::new(value, index))
+    );
+    let result = match self.0.consume(&mut tokens, context) {
+      Err(HingeError::Multiple { mut errors, partial }) => {
+        errors.extend(tokens.next().map(|token| context.unprocessed(&token)));
+        return Err(HingeError::Multiple { errors, partial })
+      },
+      result => result?
+    };
     if result.is_empty() {
-      return Err("expecting consumer to consume the tokens".to_string().into())
+      return Err(HingeError::new(HingeErrorKind::UnexpectedToken, "expecting consumer to consume the tokens"))
     }
     match tokens.next() {
-      Some(token) => Err(format!("not every token could be processed, next is: {}", token).into()),
+      Some(token) => Err(context.unprocessed(&token)),
       None => Ok(result),
     }
   }
 
+  fn suggest(&self, error: HingeError) -> HingeError {
+    if let HingeError::Multiple { errors, partial } = error {
+      let errors = errors.into_iter().map(|error| self.suggest(error)).collect();
+      return HingeError::Multiple { errors, partial }
+    }
+    let token = match error.parse_error().filter(|x| x.suggestion().is_none()).and_then(|x| x.token()) {
+      Some(token) => token.to_string(),
+      None => return error
+    };
+    let help = self.help_info();
+    match suggest(token, help.collect_names()) {
+      Some(suggestion) => error.with_suggestion(suggestion),
+      None => error
+    }
+  }
+
   pub fn apply_args(&self) -> Result<HingeOutput> {
     self.apply_tokens(std::env::args().skip(1))
   }
 
+  pub fn decide(&self, tokens: Vec<String>) -> Outcome {
+    self.decide_in(tokens).0
+  }
+
+  fn decide_in(&self, tokens: Vec<String>) -> (Outcome, ParseContext) {
+    let mut context = ParseContext::new();
+    context.set_color(self.help_info().color().unwrap_or(ColorChoice::Auto));
+    let result = self.apply_tokens_checked(tokens.clone().into_iter(), &mut context);
+    (self.outcome(result, &tokens, &context), context)
+  }
+
+  fn terminal_help(&self, context: &ParseContext) -> HingeHelp {
+    let mut help = self.help_info();
+    if let Some(color) = context.color() {
+      help.set_color(color);
+    }
+    help
+  }
+
+  fn outcome(&self, result: Result<HingeOutput>, tokens: &[String], context: &ParseContext) -> Outcome {
+    let error = match result {
+      Ok(HingeOutput::ShortCircuit(_)) => return Outcome::Exit { code: EX_OK, stream: Stream::Stdout, message: String::new() },
+      Ok(output) => return Outcome::Parsed(output),
+      Err(error) => error
+    };
+    match error.errors().into_iter().find(|x| x.is_display_request()) {
+      Some(request) => Outcome::Exit {
+        code: request.exit_code(),
+        stream: Stream::Stdout,
+        message: request.parse_error().map(|x| x.message().to_string()).unwrap_or_default()
+      },
+      None => {
+        let help = self.terminal_help(context);
+        let theme = help.theme_for(Stream::Stderr);
+        Outcome::Exit {
+          code: error.exit_code(),
+          stream: Stream::Stderr,
+          message: format!("{}\n\n{}", error.render_with(tokens, &theme), help.usage_with(&theme))
+        }
+      }
+    }
+  }
+
+  pub fn decide_args(&self) -> Outcome {
+    self.decide(std::env::args().skip(1).collect())
+  }
+
+  pub fn parse_or_exit(&self) -> HingeOutput {
+    let (outcome, context) = self.decide_in(std::env::args().skip(1).collect());
+    let theme = self.terminal_help(&context).theme_for(Stream::Stderr);
+    for diagnostic in context.diagnostics() {
+      eprintln!("{} {}", theme.get_warning().paint("warning:"), diagnostic.message());
+    }
+    outcome.or_exit()
+  }
+
   pub fn extract(self) -> Box<dyn HingeConsumer> {
     self.0
   }
 
-  pub fn help(&self) -> String {
+  pub fn help_info(&self) -> HingeHelp {
     let mut help = HingeHelp::new();
     self.0.apply_help_info(&mut help);
-    help.generate()
+    help
+  }
+
+  pub fn usage(&self) -> String {
+    self.help_info().usage()
+  }
+
+  pub fn help(&self) -> String {
+    self.help_info().generate()
+  }
+
+  pub fn long_help(&self) -> String {
+    self.help_info().generate_long()
+  }
+
+  pub fn help_json(&self) -> String {
+    self.help_info().render_with(&JsonRenderer, false)
   }
 }
 
@@ -39,4 +158,110 @@ impl<T : HingeConsumer + 'static> From<T> for Hinge {
   fn from(value: T) -> Self {
     Self::new(value)
   }
-}
\ No newline at end of file
+}
+
+const _: () = {
+  const fn assert_thread_safe<T: Send + Sync>() { }
+  assert_thread_safe::<Hinge>();
+  assert_thread_safe::<HingeOutput>();
+  assert_thread_safe::<HingeError>();
+};
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+  use crate::{ColorChoice, HingeBuilder, Hinge, HingeOutput, Outcome, Stream, EX_DATAERR, EX_OK, EX_USAGE};
+  use crate::testing::tokens;
+
+  fn hinge() -> Hinge {
+    HingeBuilder::new().program("prog").bool("v", 'v').color_flag("color").build()
+  }
+
+  fn decide(hinge: &Hinge, args: &[&str]) -> String {
+    hinge.decide(tokens(args)).message().to_string()
+  }
+
+  #[test]
+  fn string_apis_are_plain_unless_color_is_explicit() {
+    assert!(!hinge().help().contains('\x1b'));
+    assert!(!hinge().usage().contains('\x1b'));
+    let styled = HingeBuilder::new().program("prog").color(ColorChoice::Always).build();
+    assert!(styled.help().contains('\x1b'));
+  }
+
+  #[test]
+  fn color_flag_applies_to_help_and_errors() {
+    let hinge = hinge();
+    assert!(decide(&hinge, &["--help", "--color", "always"]).contains('\x1b'));
+    assert!(decide(&hinge, &["--color", "always", "-x"]).starts_with("\x1b[1;31merror:"));
+    assert!(decide(&hinge, &["--color", "never", "-x"]).starts_with("error:"));
+    let forced = HingeBuilder::new().program("prog").color(ColorChoice::Always).color_flag("color").build();
+    assert!(!decide(&forced, &["--color", "never", "--help"]).contains('\x1b'));
+  }
+
+  #[test]
+  fn color_flag_is_parsed_as_an_option() {
+    let output = hinge().decide(vec!["--color".to_string(), "never".to_string()]).or_exit();
+    assert!(output.get_item("color").is_err());
+    assert!(decide(&hinge(), &["--color", "blue"]).contains("invalid value `blue`"));
+  }
+
+  #[test]
+  fn outcomes_map_to_sysexits_codes_and_streams() {
+    let hinge = HingeBuilder::new()
+      .program("prog")
+      .version("1.0")
+      .item("n", "n").possible_values(["1", "2"]).require()
+      .build();
+    let exit = |args: &[&str]| match hinge.decide(tokens(args)) {
+      Outcome::Exit { code, stream, message } => (code, stream, message),
+      Outcome::Parsed(output) => panic!("parsed: {:?}", output)
+    };
+    let (code, stream, message) = exit(&["--n", "1", "-x"]);
+    assert_eq!((code, stream), (EX_USAGE, Stream::Stderr));
+    assert!(message.starts_with("error:"), "{}", message);
+    assert!(message.ends_with("Usage: prog [OPTIONS] --n <N>"), "{}", message);
+    assert_eq!(exit(&[]).0, EX_USAGE);
+    assert_eq!(exit(&["--n", "3"]).0, EX_DATAERR);
+    let (code, stream, message) = exit(&["--help"]);
+    assert_eq!((code, stream), (EX_OK, Stream::Stdout));
+    assert!(message.contains("Usage: prog"), "{}", message);
+    let (code, stream, message) = exit(&["-V"]);
+    assert_eq!((code, stream, message.as_str()), (EX_OK, Stream::Stdout, "1.0"));
+    assert!(hinge.decide(tokens(&["--n", "2"])).is_parsed());
+  }
+
+  #[test]
+  fn actions_run_and_short_circuit_validation() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let hinge = HingeBuilder::new()
+      .action("licenses", "licenses", move || { counter.fetch_add(1, Ordering::SeqCst); })
+      .item("n", "n").require()
+      .build();
+    let output = hinge.apply_tokens(tokens(&["--licenses"]).into_iter()).unwrap();
+    assert!(matches!(output, HingeOutput::ShortCircuit(ref id) if id == "licenses"), "{:?}", output);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(matches!(hinge.decide(tokens(&["--licenses"])), Outcome::Exit { code: 0, .. }));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+  }
+
+  #[test]
+  fn help_json_flag_reaches_subcommands() {
+    let hinge = HingeBuilder::new()
+      .program("prog")
+      .help_json_flag("help-json")
+      .item("n", "n").require()
+      .subcommand("run", "run", HingeBuilder::new().subcommand("fast", "fast", HingeBuilder::new()))
+      .build();
+    let json = |args: &[&str]| match hinge.decide(tokens(args)) {
+      Outcome::Exit { code: EX_OK, stream: Stream::Stdout, message } => message,
+      outcome => panic!("{:?}", outcome)
+    };
+    assert!(json(&["--help-json"]).starts_with(r#"{"program":"prog","#));
+    assert!(json(&["run", "--help-json"]).starts_with(r#"{"program":"prog run","#));
+    assert!(json(&["run", "fast", "--help-json"]).starts_with(r#"{"program":"prog run fast","#));
+    assert_eq!(hinge.decide(tokens(&["run", "--", "--help-json"])).exit_code(), Some(EX_USAGE));
+  }
+}
@@ -1,28 +1,111 @@
-use std::{rc::Rc, fmt::Debug, iter, mem::swap, collections};
+use std::{sync::Arc, fmt::{self, Debug}, iter, mem::swap, collections, ops::Deref};
 
-use crate::{HingeOutput, Result, HingeCollectionBuilder, HingeHelp};
+use crate::{JsonRenderer, HelpRenderer, ColorChoice, Theme, Diagnostic, ParseContext, HingeOutput, Result, HingeCollectionBuilder, HingeHelp, HingeError, HingeErrorKind, HelpArity, suggest};
 
-pub type Token = String;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+  value: String,
+  index: usize
+}
+
+impl Token {
+  pub fn new(value: impl AsRef<str>, index: usize) -> Self {
+    Token { value: value.as_ref().to_string(), index }
+  }
+
+  pub fn index(&self) -> usize {
+    self.index
+  }
+
+  pub fn into_value(self) -> String {
+    self.value
+  }
+}
+
+impl Deref for Token {
+  type Target = str;
+
+  fn deref(&self) -> &Self::Target {
+    &self.value
+  }
+}
+
+impl PartialEq<&str> for Token {
+  fn eq(&self, other: &&str) -> bool {
+    self.value == *other
+  }
+}
 
-pub trait HingeConsumer: Debug {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput>;
+impl fmt::Display for Token {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(&self.value, f)
+  }
+}
+
+impl From<Token> for HingeOutput {
+  fn from(value: Token) -> Self {
+    HingeOutput::Value(value.into_value())
+  }
+}
+
+fn prepend(iterator: &mut Box<dyn Iterator<Item = Token>>, tokens: Vec<Token>) {
+  let mut tmp: Box<dyn Iterator<Item = Token>> = Box::new(iter::empty());
+  swap(&mut tmp, iterator);
+  *iterator = Box::new(tokens.into_iter().chain(tmp));
+}
+
+fn is_option_like(token: &Token) -> bool {
+  token.starts_with('-') && *token != "-" && *token != ClassificationNode::TERMINATOR
+}
+
+fn unknown_option(iterator: &mut Box<dyn Iterator<Item = Token>>) -> Option<HingeError> {
+  let token = iterator.next()?;
+  let error = is_option_like(&token).then(|| HingeError::unprocessed(&token));
+  prepend(iterator, vec![token]);
+  error
+}
+
+pub trait HingeConsumer: Debug + Send + Sync {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput>;
 
   fn apply_help_info(&self, _: &mut HingeHelp) { }
+
+  fn known_names(&self) -> Vec<String> {
+    Vec::new()
+  }
 }
 
 impl<T : HingeConsumer + ?Sized> HingeConsumer for Box<T> {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
-    (**self).consume(iterator)
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    (**self).consume(iterator, context)
   }
 
   fn apply_help_info(&self, help: &mut HingeHelp) {
     (**self).apply_help_info(help)
   }
+
+  fn known_names(&self) -> Vec<String> {
+    (**self).known_names()
+  }
 }
 
-pub trait HelpFn : Fn(&mut HingeHelp) + 'static { }
+impl<T : HingeConsumer + ?Sized> HingeConsumer for Arc<T> {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    (**self).consume(iterator, context)
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    (**self).apply_help_info(help)
+  }
 
-impl<U: Fn(&mut HingeHelp) + 'static> HelpFn for U { }
+  fn known_names(&self) -> Vec<String> {
+    (**self).known_names()
+  }
+}
+
+pub trait HelpFn : Fn(&mut HingeHelp) + Send + Sync + 'static { }
+
+impl<U: Fn(&mut HingeHelp) + Send + Sync + 'static> HelpFn for U { }
 
 impl Debug for dyn HelpFn {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -30,23 +113,33 @@ impl Debug for dyn HelpFn {
   }
 }
 
+pub trait ActionFn : Fn() + Send + Sync + 'static { }
+
+impl<U: Fn() + Send + Sync + 'static> ActionFn for U { }
+
+impl Debug for dyn ActionFn {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "ActionFn()")
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct HelpNode {
-  child: Rc<Box<dyn HingeConsumer>>,
-  actions: Vec<Rc<Box<dyn HelpFn>>>
+  child: Arc<Box<dyn HingeConsumer>>,
+  actions: Vec<Arc<Box<dyn HelpFn>>>
 }
 
 impl HelpNode {
   pub fn new(child: impl HingeConsumer + 'static) -> Self {
-    HelpNode { child: Rc::new(Box::new(child)), actions: Vec::new() }
+    HelpNode { child: Arc::new(Box::new(child)), actions: Vec::new() }
   }
 
   pub fn new_with(child: impl HingeConsumer + 'static, action: impl HelpFn + 'static) -> Self {
-    HelpNode { child: Rc::new(Box::new(child)), actions: vec![Rc::new(Box::new(action))] }
+    HelpNode { child: Arc::new(Box::new(child)), actions: vec![Arc::new(Box::new(action))] }
   }
 
   pub fn add(&mut self, action: impl HelpFn + 'static) {
-    self.actions.push(Rc::new(Box::new(action)))
+    self.actions.push(Arc::new(Box::new(action)))
   }
 
   pub fn and(mut self, action: impl HelpFn + 'static) -> Self {
@@ -59,6 +152,21 @@ impl HelpNode {
     self.and(move |help: &mut HingeHelp| help.set_description(&msg))
   }
 
+  pub fn long_description(self, description: impl AsRef<str>) -> Self {
+    let msg: String = description.as_ref().to_string();
+    self.and(move |help: &mut HingeHelp| help.set_long_description(&msg))
+  }
+
+  pub fn annotation(self, annotation: impl AsRef<str>) -> Self {
+    let msg: String = annotation.as_ref().to_string();
+    self.and(move |help: &mut HingeHelp| help.add_annotation(&msg))
+  }
+
+  pub fn heading(self, heading: impl AsRef<str>) -> Self {
+    let heading: String = heading.as_ref().to_string();
+    self.and(move |help: &mut HingeHelp| help.set_heading(&heading))
+  }
+
   pub fn tabulate(self) -> Self {
     self.and(move |help: &mut HingeHelp| help.set_tabulate_childs(true))
   }
@@ -69,8 +177,8 @@ impl HelpNode {
 }
 
 impl HingeConsumer for HelpNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
-    self.child.consume(iterator)
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    self.child.consume(iterator, context)
   }
 
   fn apply_help_info(&self, help: &mut HingeHelp) {
@@ -79,13 +187,407 @@ impl HingeConsumer for HelpNode {
     }
     self.child.apply_help_info(help)
   }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct HiddenNode(Arc<Box<dyn HingeConsumer>>);
+
+impl HiddenNode {
+  pub fn new(child: impl HingeConsumer + 'static) -> Self {
+    HiddenNode(Arc::new(Box::new(child)))
+  }
+}
+
+impl HingeConsumer for HiddenNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    self.0.consume(iterator, context)
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    help.set_hidden(true)
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.0.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeprecatedNode {
+  child: Arc<Box<dyn HingeConsumer>>,
+  replacement: Option<String>
+}
+
+impl DeprecatedNode {
+  pub fn new(child: impl HingeConsumer + 'static) -> Self {
+    DeprecatedNode { child: Arc::new(Box::new(child)), replacement: None }
+  }
+
+  pub fn replacement(mut self, replacement: impl AsRef<str>) -> Self {
+    self.replacement = Some(replacement.as_ref().to_string());
+    self
+  }
+}
+
+impl HingeConsumer for DeprecatedNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    let token = match iterator.next() {
+      Some(token) => {
+        prepend(iterator, vec![token.clone()]);
+        token
+      },
+      None => return self.child.consume(iterator, context)
+    };
+    let output = self.child.consume(iterator, context)?;
+    if !output.is_empty() {
+      let message = match &self.replacement {
+        Some(replacement) => format!("{} is deprecated, use {} instead", token, replacement),
+        None => format!("{} is deprecated", token)
+      };
+      context.report(Diagnostic::new(message).at(&token));
+    }
+    Ok(output)
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    self.child.apply_help_info(help);
+    match &self.replacement {
+      Some(replacement) => help.add_annotation(format!("deprecated, use {}", replacement)),
+      None => help.add_annotation("deprecated")
+    }
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContextNode {
+  context: String,
+  child: Arc<Box<dyn HingeConsumer>>
+}
+
+impl ContextNode {
+  pub fn new(context: impl AsRef<str>, child: impl HingeConsumer + 'static) -> Self {
+    ContextNode { context: context.as_ref().to_string(), child: Arc::new(Box::new(child)) }
+  }
+}
+
+impl HingeConsumer for ContextNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    self.child.consume(iterator, context).map_err(|error| error.context(&self.context))
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    self.child.apply_help_info(help)
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubcommandNode {
+  name: String,
+  child: Arc<Box<dyn HingeConsumer>>
+}
+
+impl SubcommandNode {
+  pub fn new(name: impl AsRef<str>, child: impl HingeConsumer + 'static) -> Self {
+    SubcommandNode { name: name.as_ref().to_string(), child: Arc::new(Box::new(child)) }
+  }
+}
+
+impl HingeConsumer for SubcommandNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    self.child.consume(iterator, context).map_err(|error| error.map(|error| match error.kind() {
+      Some(HingeErrorKind::DisplayHelp | HingeErrorKind::DisplayHelpJson) => error.within(&self.name),
+      _ => error
+    }))
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    self.child.apply_help_info(help)
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct HelpCommandNode;
+
+impl HingeConsumer for HelpCommandNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
+    let path = iterator.map(Token::into_value).collect();
+    Err(HingeError::new(HingeErrorKind::DisplayHelp, "").with_path(path))
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplayNode(HingeErrorKind);
+
+impl DisplayNode {
+  pub fn new(kind: HingeErrorKind) -> Self {
+    DisplayNode(kind)
+  }
+}
+
+impl HingeConsumer for DisplayNode {
+  fn consume(&self, _: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
+    Err(HingeError::new(self.0, ""))
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActionNode {
+  id: String,
+  action: Arc<Box<dyn ActionFn>>
+}
+
+impl ActionNode {
+  pub fn new(id: impl AsRef<str>, action: impl ActionFn + 'static) -> Self {
+    ActionNode { id: id.as_ref().to_string(), action: Arc::new(Box::new(action)) }
+  }
+}
+
+impl HingeConsumer for ActionNode {
+  fn consume(&self, _: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
+    (self.action)();
+    Err(HingeError::new(HingeErrorKind::ShortCircuit, format!("short-circuited by {}", self.id)).with_id(&self.id))
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct HelpScopeNode {
+  child: Arc<Box<dyn HingeConsumer>>,
+  program: Option<String>,
+  version: Option<String>,
+  about: Option<String>,
+  long_about: Option<String>,
+  after_help: Option<String>,
+  width: Option<usize>,
+  sorted: bool,
+  color: Option<ColorChoice>,
+  color_flag: Vec<String>,
+  help_json_flag: Vec<String>,
+  theme: Option<Theme>,
+  renderer: Option<Arc<dyn HelpRenderer>>
+}
+
+impl HelpScopeNode {
+  pub fn new(child: impl HingeConsumer + 'static) -> Self {
+    HelpScopeNode {
+      child: Arc::new(Box::new(child)),
+      program: None,
+      version: None,
+      about: None,
+      long_about: None,
+      after_help: None,
+      width: None,
+      sorted: false,
+      color: None,
+      color_flag: Vec::new(),
+      help_json_flag: Vec::new(),
+      theme: None,
+      renderer: None
+    }
+  }
+
+  pub fn about(mut self, about: impl AsRef<str>) -> Self {
+    self.about = Some(about.as_ref().to_string());
+    self
+  }
+
+  pub fn long_about(mut self, about: impl AsRef<str>) -> Self {
+    self.long_about = Some(about.as_ref().to_string());
+    self
+  }
+
+  pub fn after_help(mut self, after_help: impl AsRef<str>) -> Self {
+    self.after_help = Some(after_help.as_ref().to_string());
+    self
+  }
+
+  pub fn sorted(mut self) -> Self {
+    self.sorted = true;
+    self
+  }
+
+  pub fn color(mut self, color: ColorChoice) -> Self {
+    self.color = Some(color);
+    self
+  }
+
+  pub fn color_flag(mut self, names: Vec<impl AsRef<str>>) -> Self {
+    self.color_flag = names.into_iter().map(|x| x.as_ref().to_string()).collect();
+    self
+  }
+
+  pub fn help_json_flag(mut self, names: Vec<impl AsRef<str>>) -> Self {
+    self.help_json_flag = names.into_iter().map(|x| x.as_ref().to_string()).collect();
+    self
+  }
+
+  pub fn theme(mut self, theme: Theme) -> Self {
+    self.theme = Some(theme);
+    self
+  }
+
+  pub fn renderer(mut self, renderer: impl HelpRenderer + 'static) -> Self {
+    self.renderer = Some(Arc::new(renderer));
+    self
+  }
+
+  pub fn width(mut self, width: usize) -> Self {
+    self.width = Some(width);
+    self
+  }
+
+  pub fn program(mut self, program: impl AsRef<str>) -> Self {
+    self.program = Some(program.as_ref().to_string());
+    self
+  }
+
+  pub fn version(mut self, version: impl AsRef<str>) -> Self {
+    self.version = Some(version.as_ref().to_string());
+    self
+  }
+
+  fn scan_color(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) {
+    if self.color_flag.is_empty() {
+      return
+    }
+    let tokens: Vec<Token> = iterator.collect();
+    let choice = tokens.iter()
+      .take_while(|x| **x != ClassificationNode::TERMINATOR)
+      .zip(tokens.iter().skip(1))
+      .filter(|(flag, _)| self.color_flag.iter().any(|x| **flag == x.as_str()))
+      .filter_map(|(_, value)| value.parse::<ColorChoice>().ok())
+      .last();
+    if let Some(choice) = choice {
+      context.set_color(choice);
+    }
+    prepend(iterator, tokens);
+  }
+
+  fn fill(&self, error: HingeError, context: &ParseContext) -> HingeError {
+    let path = match error.parse_error() {
+      Some(parse) if parse.message().is_empty() || !parse.path().is_empty() => parse.path().to_vec(),
+      _ => return error
+    };
+    match error.kind() {
+      Some(HingeErrorKind::DisplayHelp) => {
+        let mut help = HingeHelp::new();
+        self.apply_help_info(&mut help);
+        if let Some(color) = context.color() {
+          help.set_color(color);
+        }
+        let long = error.parse_error().and_then(|x| x.token()).is_some_and(|x| x.starts_with("--"));
+        match help.command_help(&path) {
+          Some(help) if long => error.with_message(help.generate_long()),
+          Some(help) => error.with_message(help.generate()),
+          None => HingeError::new(
+            HingeErrorKind::UnknownArgument,
+            format!("no help available for unknown subcommand: {}", path.join(" "))
+          )
+        }
+      },
+      Some(HingeErrorKind::DisplayHelpJson) => {
+        let mut help = HingeHelp::new();
+        self.apply_help_info(&mut help);
+        match help.command_help(&path) {
+          Some(help) => error.with_message(help.render_with(&JsonRenderer, false)),
+          None => HingeError::new(
+            HingeErrorKind::UnknownArgument,
+            format!("no help available for unknown subcommand: {}", path.join(" "))
+          )
+        }
+      },
+      Some(HingeErrorKind::DisplayVersion) => error.with_message(self.version.clone().unwrap_or_default()),
+      _ => error
+    }
+  }
+}
+
+impl HingeConsumer for HelpScopeNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    self.scan_color(iterator, context);
+    context.inherit_help_json_flag(&self.help_json_flag);
+    self.child.consume(iterator, context).map_err(|error| error.map(|error| self.fill(error, context)))
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    if let Some(program) = &self.program {
+      help.set_program(program);
+    }
+    if let Some(about) = &self.about {
+      help.set_description(about);
+    }
+    if let Some(about) = &self.long_about {
+      help.set_long_description(about);
+    }
+    if let Some(after_help) = &self.after_help {
+      help.set_after_help(after_help);
+    }
+    if let Some(width) = self.width {
+      help.set_width(width);
+    }
+    if self.sorted {
+      help.set_sorted(true);
+    }
+    if let Some(color) = self.color {
+      help.set_color(color);
+    }
+    if let Some(theme) = self.theme {
+      help.set_theme(theme);
+    }
+    if let Some(renderer) = &self.renderer {
+      help.set_renderer(renderer.clone());
+    }
+    self.child.apply_help_info(help)
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct FailNode {
+  kind: HingeErrorKind,
+  message: String
+}
+
+impl FailNode {
+  pub fn new(kind: HingeErrorKind, message: impl AsRef<str>) -> Self {
+    FailNode { kind, message: message.as_ref().to_string() }
+  }
+}
+
+impl HingeConsumer for FailNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
+    let error = HingeError::new(self.kind, &self.message);
+    match iterator.next() {
+      Some(token) if is_option_like(&token) => Err(HingeError::unprocessed(&token)),
+      Some(token) => Err(error.at(&token)),
+      None => Err(error)
+    }
+  }
 }
 
 #[derive(Debug, Clone)]
 pub struct AlwaysTrueNode;
 
 impl HingeConsumer for AlwaysTrueNode {
-  fn consume(&self, _: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
+  fn consume(&self, _: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
     Ok(HingeOutput::True)
   }
 }
@@ -94,20 +596,28 @@ impl HingeConsumer for AlwaysTrueNode {
 pub struct OneTokenNode;
 
 impl HingeConsumer for OneTokenNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
     match iterator.next() {
-      Some(val) => Ok(HingeOutput::Value(val)),
-      None => Err("expecting a value".to_string().into())
+      Some(val) => Ok(val.into()),
+      None => Err(HingeError::new(HingeErrorKind::MissingValue, "expecting a value"))
     }
   }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    help.set_arity(HelpArity::One);
+  }
 }
 
 #[derive(Debug, Clone)]
 pub struct OptionalTokenNode;
 
 impl HingeConsumer for OptionalTokenNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
-    Ok(iterator.next().map(|val| HingeOutput::Value(val)).unwrap_or(HingeOutput::Empty))
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, _: &mut ParseContext) -> Result<HingeOutput> {
+    Ok(iterator.next().map(HingeOutput::from).unwrap_or(HingeOutput::Empty))
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    help.set_arity(HelpArity::One);
   }
 }
 
@@ -123,50 +633,58 @@ impl ListNode {
 }
 
 impl HingeConsumer for ListNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
     let result: Vec<_> = {
       if let Some(count) = self.count {
-        let collected: Vec<_> = iterator.take(count).map(|x| HingeOutput::Value(x)).collect();
+        let collected: Vec<_> = iterator.take(count).map(HingeOutput::from).collect();
         if collected.len() < count {
-          return Err("not enough elements".to_string().into());
+          return Err(HingeError::new(HingeErrorKind::TooFewValues, "not enough elements"));
+        }
+        if let Some(token) = iterator.next() {
+          if !token.starts_with('-') || token == "-" {
+            context.mark_surplus(&token, None);
+          }
+          prepend(iterator, vec![token]);
         }
         collected
       } else {
-        iterator.map(|x| HingeOutput::Value(x)).collect()
+        iterator.map(HingeOutput::from).collect()
       }
     };
-    return Ok(HingeOutput::List(result))
+    Ok(HingeOutput::List(result))
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    help.set_arity(HelpArity::Many(self.count));
   }
 }
 
 #[derive(Debug, Clone)]
 pub struct NamedNode {
   names: Vec<String>,
-  wrapped: Rc<Box<dyn HingeConsumer>>
+  wrapped: Arc<Box<dyn HingeConsumer>>
 }
 
 impl NamedNode {
   pub fn new(names: Vec<impl AsRef<str>>, child_consumer: impl HingeConsumer + 'static) -> Self {
     NamedNode {
       names: names.into_iter().map(|x| x.as_ref().to_string()).collect(),
-      wrapped: Rc::new(Box::new(child_consumer))
+      wrapped: Arc::new(Box::new(child_consumer))
     }
   }
 }
 
 impl HingeConsumer for NamedNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
     let first_token = match iterator.next() {
       Some(n) => n,
       None => return Ok(HingeOutput::Empty),
     };
-    if !self.names.iter().any(|x| *x == first_token) {
-      let mut tmp: Box<dyn Iterator<Item = Token>> = Box::new(iter::empty());
-      swap(&mut tmp, iterator);
-      *iterator = Box::new(iter::once(first_token).chain(tmp));
+    if !self.names.iter().any(|x| first_token == x.as_str()) {
+      prepend(iterator, vec![first_token]);
       return Ok(HingeOutput::Empty)
     }
-    return self.wrapped.consume(iterator)
+    self.wrapped.consume(iterator, context).map_err(|error| error.or_at(&first_token))
   }
 
   fn apply_help_info(&self, help: &mut HingeHelp) {
@@ -175,19 +693,153 @@ impl HingeConsumer for NamedNode {
     }
     self.wrapped.apply_help_info(help);
   }
+
+  fn known_names(&self) -> Vec<String> {
+    self.names.clone()
+  }
 }
 
-#[derive(Debug, Clone)]
+type ClassificationEntry = (String, Arc<Box<dyn HingeConsumer>>);
+
+#[derive(Debug, Clone, Default)]
 pub struct ClassificationNode {
-  entries: (
-    Vec<(String, Rc<Box<dyn HingeConsumer>>)>,
-    Vec<(String, Rc<Box<dyn HingeConsumer>>)>
-  )
+  entries: (Vec<ClassificationEntry>, Vec<ClassificationEntry>),
+  builtins: Vec<ClassificationEntry>,
+  tail: Option<String>,
+  operands: Option<String>,
+  conflicts: Vec<(String, String)>,
+  abbreviate: bool,
+  collect_errors: bool
 }
 
 impl ClassificationNode {
+  pub const TERMINATOR: &str = "--";
+
   pub fn new() -> Self {
-    ClassificationNode { entries: (Vec::new(), Vec::new()) }
+    ClassificationNode {
+      entries: (Vec::new(), Vec::new()),
+      builtins: Vec::new(),
+      tail: None,
+      operands: None,
+      conflicts: Vec::new(),
+      abbreviate: false,
+      collect_errors: false
+    }
+  }
+
+  pub fn set_tail(&mut self, id: impl AsRef<str>) {
+    self.tail = Some(id.as_ref().to_string());
+  }
+
+  pub fn set_stop_at_operand(&mut self, id: impl AsRef<str>) {
+    self.operands = Some(id.as_ref().to_string());
+  }
+
+  pub fn add_conflict(&mut self, id: impl AsRef<str>, other: impl AsRef<str>) {
+    self.conflicts.push((id.as_ref().to_string(), other.as_ref().to_string()));
+  }
+
+  fn check_conflicts(&self, seen: &collections::HashMap<String, Token>) -> Vec<HingeError> {
+    self.conflicts.iter().filter_map(|(id, other)| {
+      let (first, second) = match (seen.get(id), seen.get(other)) {
+        (Some(a), Some(b)) if a.index() <= b.index() => ((id, a), (other, b)),
+        (Some(a), Some(b)) => ((other, b), (id, a)),
+        _ => return None
+      };
+      let message = format!("{} cannot be used with {}", second.1, first.1);
+      Some(HingeError::new(HingeErrorKind::Conflict, message).with_id(second.0).at(second.1).with_related(first.1.index()))
+    }).collect()
+  }
+
+  pub fn set_abbreviate(&mut self, abbreviate: bool) {
+    self.abbreviate = abbreviate;
+  }
+
+  pub fn set_collect_errors(&mut self, collect_errors: bool) {
+    self.collect_errors = collect_errors;
+  }
+
+  fn skip_unknown(iterator: &mut Box<dyn Iterator<Item = Token>>) -> Option<Token> {
+    match iterator.next() {
+      Some(token) if is_option_like(&token) => Some(token),
+      Some(token) => {
+        prepend(iterator, vec![token]);
+        None
+      },
+      None => None
+    }
+  }
+
+  fn expand_abbreviation(iterator: &mut Box<dyn Iterator<Item = Token>>, long_names: &[String]) -> Result<()> {
+    if long_names.is_empty() {
+      return Ok(())
+    }
+    let token = match iterator.next() {
+      Some(token) => token,
+      None => return Ok(())
+    };
+    let candidates: Vec<&str> = if token.len() > 2 && token.starts_with("--") && !long_names.iter().any(|x| token == x.as_str()) {
+      long_names.iter().filter(|x| x.starts_with(&*token)).map(|x| x.as_str()).collect()
+    } else {
+      Vec::new()
+    };
+    match candidates.as_slice() {
+      [] => prepend(iterator, vec![token]),
+      [name] => prepend(iterator, vec![Token::new(name, token.index())]),
+      _ => return Err(HingeError::new(
+        HingeErrorKind::UnknownArgument,
+        format!("ambiguous option {}, could be: {}", token, candidates.join(", "))
+      ).at(&token))
+    }
+    Ok(())
+  }
+
+  fn take_operands(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, builder: &mut HingeCollectionBuilder, positional_only: bool) {
+    let id = match &self.operands {
+      Some(id) => id,
+      None => return
+    };
+    match iterator.next() {
+      Some(token) if positional_only || !token.starts_with('-') || token == "-" => {
+        let operands = iter::once(token).chain(iterator.by_ref()).map(HingeOutput::from).collect();
+        builder.add_item(id, HingeOutput::List(operands));
+      },
+      Some(token) => prepend(iterator, vec![token]),
+      None => ()
+    }
+  }
+
+  fn mark_surplus(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, builder: &HingeCollectionBuilder, last: Option<&String>, context: &mut ParseContext) {
+    let token = match iterator.next() {
+      Some(token) => token,
+      None => return
+    };
+    if !token.starts_with('-') || token == "-" {
+      match self.entries.1.last() {
+        Some(positional) if self.entries.1.iter().all(|x| builder.has_item(&x.0)) => context.mark_surplus(&token, Some(&positional.0)),
+        _ if context.is_surplus(&token) => context.mark_surplus(&token, last.map(String::as_str)),
+        _ => ()
+      }
+    }
+    prepend(iterator, vec![token]);
+  }
+
+  fn take_terminator(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, builder: &mut HingeCollectionBuilder) -> bool {
+    match iterator.next() {
+      Some(token) if token == Self::TERMINATOR => {
+        if let Some(tail) = &self.tail {
+          let rest: Vec<Token> = iterator.collect();
+          builder.add_item(tail, HingeOutput::List(rest.iter().cloned().map(HingeOutput::from).collect()));
+          prepend(iterator, rest);
+        }
+        true
+      },
+      Some(token) => {
+        prepend(iterator, vec![token]);
+        false
+      },
+      None => false
+    }
   }
 
   pub fn put(&mut self, id: impl AsRef<str>, value: impl HingeConsumer + 'static, prioritary: bool) {
@@ -195,91 +847,431 @@ impl ClassificationNode {
       &mut self.entries.0
     } else {
       &mut self.entries.1
-    }.push((id.as_ref().to_string(), Rc::new(Box::new(value))));
+    }.push((id.as_ref().to_string(), Arc::new(Box::new(value))));
+  }
+
+  pub fn put_builtin(&mut self, id: impl AsRef<str>, value: impl HingeConsumer + 'static) {
+    self.builtins.push((id.as_ref().to_string(), Arc::new(Box::new(value))));
+  }
+
+  pub fn map_entry<T: HingeConsumer + 'static>(&mut self, id: impl AsRef<str>, f: impl FnOnce(Arc<Box<dyn HingeConsumer>>) -> T) {
+    if let Some(entry) = self.entries.0.iter_mut().chain(self.entries.1.iter_mut()).find(|x| x.0 == id.as_ref()) {
+      entry.1 = Arc::new(Box::new(f(entry.1.clone())));
+    }
+  }
+
+  pub fn has_positionals(&self) -> bool {
+    !self.entries.1.is_empty() || self.tail.is_some() || self.operands.is_some()
   }
 
-  pub fn all_entries(&self) -> impl Iterator<Item = &(String, Rc<Box<dyn HingeConsumer>>)> {
-    self.entries.0.iter().chain(self.entries.1.iter())
+  pub fn all_entries(&self) -> impl Iterator<Item = &ClassificationEntry> {
+    self.entries.0.iter().chain(self.builtins.iter()).chain(self.entries.1.iter())
   }
 }
 
 impl HingeConsumer for ClassificationNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
     let mut builder = HingeCollectionBuilder::new();
+    let mut builtins = HingeCollectionBuilder::new();
+    let mut errors = Vec::new();
+    let mut positional_only = false;
+    let mut seen = collections::HashMap::new();
+    let mut last = None;
+    let long_names: Vec<String> = match self.abbreviate {
+      true => self.known_names().into_iter().filter(|x| x.starts_with("--")).collect(),
+      false => Vec::new()
+    };
     loop {
-      let results: Result<Vec<_>> = self.all_entries()
-        .filter(|item| !builder.has_item(&item.0))
-        .map(|item| item.1.consume(iterator).map(|x| (item, x)))
-        .collect();
-      let non_empty: Vec<_> = results?.into_iter().filter(|(_, x)| !x.is_empty()).collect();
-      if non_empty.is_empty() {
-        break;
+      let mut consumed = false;
+      let entries = self.entries.0.iter().map(|x| (x, true, false))
+        .chain(self.builtins.iter().map(|x| (x, true, true)))
+        .chain(self.entries.1.iter().map(|x| (x, false, false)));
+      for (item, prioritary, builtin) in entries {
+        positional_only = positional_only || self.take_terminator(iterator, &mut builder);
+        if !positional_only {
+          if let Err(error) = Self::expand_abbreviation(iterator, &long_names) {
+            match self.collect_errors {
+              true => errors.push(error),
+              false => return Err(error)
+            }
+          }
+        }
+        let target = match builtin {
+          true => &mut builtins,
+          false => &mut builder
+        };
+        if target.has_item(&item.0) || (positional_only && prioritary) {
+          continue;
+        }
+        let first = iterator.next();
+        prepend(iterator, first.iter().cloned().collect());
+        if !prioritary && !positional_only && first.as_ref().is_some_and(is_option_like) {
+          continue;
+        }
+        let result = match item.1.consume(iterator, context).map_err(|error| error.or_id(&item.0)) {
+          Ok(result) => result,
+          Err(error) if self.collect_errors => {
+            errors.push(error);
+            target.add_item(&item.0, HingeOutput::Empty);
+            consumed = true;
+            continue;
+          },
+          Err(error) => return Err(error)
+        };
+        if !result.is_empty() {
+          target.add_item(&item.0, result);
+          if !builtin {
+            last = Some(&item.0);
+            seen.extend(first.map(|token| (item.0.clone(), token)));
+          }
+          consumed = true;
+          positional_only = positional_only || (!prioritary && self.operands.is_some());
+        }
       }
-      for (item, result) in non_empty {
-        builder.add_item(&item.0, result);
+      if !consumed {
+        match Self::skip_unknown(iterator) {
+          Some(token) if !positional_only && context.is_help_json_flag(&token) => {
+            let error = HingeError::new(HingeErrorKind::DisplayHelpJson, "").at(&token);
+            match self.collect_errors {
+              true => errors.push(error),
+              false => return Err(error)
+            }
+          },
+          Some(token) if self.collect_errors && !positional_only => errors.push(HingeError::unprocessed(&token)),
+          Some(token) => {
+            prepend(iterator, vec![token]);
+            break;
+          },
+          None => break
+        }
       }
     }
-    for item in self.all_entries() {
-      if !builder.has_item(&item.0) {
-        builder.add_item(&item.0, HingeOutput::Empty);
+    if !positional_only {
+      positional_only = self.take_terminator(iterator, &mut builder);
+    }
+    self.take_operands(iterator, &mut builder, positional_only);
+    self.mark_surplus(iterator, &builder, last, context);
+    if self.tail.as_ref().is_some_and(|x| builder.has_item(x)) {
+      iterator.for_each(drop);
+    }
+    for error in self.check_conflicts(&seen) {
+      match self.collect_errors {
+        true => errors.push(error),
+        false => return Err(error)
       }
     }
-    Ok(builder.collect())
+    for id in self.entries.0.iter().chain(self.entries.1.iter()).map(|x| &x.0).chain(self.tail.iter()).chain(self.operands.iter()) {
+      if !builder.has_item(id) {
+        builder.add_item(id, HingeOutput::Empty);
+      }
+    }
+    match errors.is_empty() {
+      true => Ok(builder.collect()),
+      false => Err(HingeError::multiple(errors, Some(builder.collect())))
+    }
   }
 
   fn apply_help_info(&self, help: &mut HingeHelp) {
     for item in self.all_entries() {
       let child = help.get_new_child();
+      child.set_id(&item.0);
       child.set_alternative_name(format!("<{}>", item.0));
       item.1.apply_help_info(child);
     }
+    if let Some(tail) = &self.tail {
+      let child = help.get_new_child();
+      child.set_id(tail);
+      child.set_alternative_name(format!("<{}>", tail));
+      child.add_name(Self::TERMINATOR);
+    }
+    if let Some(operands) = &self.operands {
+      let child = help.get_new_child();
+      child.set_id(operands);
+      child.set_alternative_name(format!("<{}>...", operands));
+      child.set_arity(HelpArity::Many(None));
+    }
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.all_entries().flat_map(|item| item.1.known_names()).collect()
   }
 }
 
 #[derive(Debug, Clone)]
 pub struct MandatoryItemsNode {
-  child: Rc<Box<dyn HingeConsumer>>,
-  names: Vec<String>
+  child: Arc<Box<dyn HingeConsumer>>,
+  names: Vec<String>,
+  collect_errors: bool
 }
 
 impl MandatoryItemsNode {
   pub fn new(child: impl HingeConsumer + 'static, names: Vec<String>) -> Self {
-    MandatoryItemsNode { child: Rc::new(Box::new(child)), names }
+    MandatoryItemsNode { child: Arc::new(Box::new(child)), names, collect_errors: false }
+  }
+
+  pub fn collecting_errors(mut self) -> Self {
+    self.collect_errors = true;
+    self
   }
 }
 
 impl HingeConsumer for MandatoryItemsNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
-    let builder: HingeCollectionBuilder = self.child.consume(iterator)?.try_into()?;
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    let (output, mut errors) = match self.child.consume(iterator, context) {
+      Ok(output) => (output, Vec::new()),
+      Err(HingeError::Multiple { errors, partial: Some(partial) }) if self.collect_errors => (*partial, errors),
+      Err(error) => return Err(error)
+    };
+    let builder: HingeCollectionBuilder = output.try_into()?;
     for name in &self.names {
       let map: &collections::HashMap<_, _> = builder.as_ref();
-      if !map.contains_key(name) || map.get(name).unwrap().is_empty() {
-        return Err(format!("Expecting item with name: {}", name).into());
+      let failed = errors.iter().any(|error| error.parse_error().and_then(|x| x.id()) == Some(name));
+      if !failed && (!map.contains_key(name) || map.get(name).unwrap().is_empty()) {
+        if let Some(error) = unknown_option(iterator).filter(|_| !self.collect_errors) {
+          return Err(error)
+        }
+        let error = HingeError::new(
+          HingeErrorKind::MissingRequired,
+          format!("Expecting item with name: {}", name)
+        ).with_id(name);
+        match self.collect_errors {
+          true => errors.push(error),
+          false => return Err(error)
+        }
+      }
+    }
+    match errors.is_empty() {
+      true => Ok(builder.collect()),
+      false => Err(HingeError::multiple(errors, Some(builder.collect())))
+    }
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    self.child.apply_help_info(help);
+    for name in self.names.iter() {
+      if let Some(child) = help.find_child_mut(name) {
+        child.set_required(true);
+      }
+    }
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefixNode {
+  prefix: Arc<Box<dyn HingeConsumer>>,
+  child: Arc<Box<dyn HingeConsumer>>
+}
+
+impl PrefixNode {
+  pub fn new(prefix: impl HingeConsumer + 'static, child: impl HingeConsumer + 'static) -> Self {
+    PrefixNode { prefix: Arc::new(Box::new(prefix)), child: Arc::new(Box::new(child)) }
+  }
+}
+
+impl HingeConsumer for PrefixNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    let (output, mut errors) = match self.prefix.consume(iterator, context) {
+      Ok(output) => (output, Vec::new()),
+      Err(HingeError::Multiple { errors, partial: Some(partial) }) => (*partial, errors),
+      Err(error) => return Err(error)
+    };
+    let mut builder: HingeCollectionBuilder = output.try_into()?;
+    match self.child.consume(iterator, context) {
+      Ok(HingeOutput::Empty) => (),
+      Ok(output) => {
+        let (list, map): (Vec<_>, collections::HashMap<_, _>) = match output {
+          HingeOutput::Map(map) => (Vec::new(), map),
+          HingeOutput::List(list) => (list, collections::HashMap::new()),
+          HingeOutput::MapList(map, list) => (list, map),
+          output => (vec![output], collections::HashMap::new())
+        };
+        list.into_iter().for_each(|value| builder.add_value(value));
+        map.into_iter().for_each(|(id, value)| builder.add_item(id, value));
+      },
+      Err(error) if errors.is_empty() => return Err(error),
+      Err(HingeError::Multiple { errors: nested, .. }) => errors.extend(nested),
+      Err(error) => errors.push(error)
+    }
+    match errors.is_empty() {
+      true => Ok(builder.collect()),
+      false => Err(HingeError::multiple(errors, Some(builder.collect())))
+    }
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    let start = help.childs().len();
+    self.prefix.apply_help_info(help);
+    help.childs_mut()[start..].iter_mut().for_each(|x| x.set_global(true));
+    self.child.apply_help_info(help);
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.prefix.known_names().into_iter().chain(self.child.known_names()).collect()
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Fallback {
+  many: bool,
+  env: Option<String>,
+  default: Option<String>
+}
+
+impl Fallback {
+  pub fn new(many: bool) -> Self {
+    Fallback { many, env: None, default: None }
+  }
+
+  pub fn env(mut self, env: impl AsRef<str>) -> Self {
+    self.env = Some(env.as_ref().to_string());
+    self
+  }
+
+  pub fn default_value(mut self, default: impl AsRef<str>) -> Self {
+    self.default = Some(default.as_ref().to_string());
+    self
+  }
+
+  fn resolve(&self) -> Option<HingeOutput> {
+    let from_env = self.env.as_ref().and_then(|x| std::env::var(x).ok());
+    match (self.many, from_env) {
+      (true, Some(value)) => Some(HingeOutput::List(value.split_whitespace().map(|x| HingeOutput::Value(x.to_string())).collect())),
+      (false, Some(value)) => Some(HingeOutput::Value(value)),
+      (true, None) => self.default.clone().map(|x| HingeOutput::List(vec![HingeOutput::Value(x)])),
+      (false, None) => self.default.clone().map(HingeOutput::Value)
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct FallbackNode {
+  child: Arc<Box<dyn HingeConsumer>>,
+  fallbacks: Vec<(String, Fallback)>
+}
+
+impl FallbackNode {
+  pub fn new(child: impl HingeConsumer + 'static, fallbacks: Vec<(String, Fallback)>) -> Self {
+    FallbackNode { child: Arc::new(Box::new(child)), fallbacks }
+  }
+
+  fn fill(&self, output: HingeOutput) -> Result<HingeOutput> {
+    let mut builder: HingeCollectionBuilder = output.try_into()?;
+    for (id, fallback) in self.fallbacks.iter() {
+      let map: &collections::HashMap<_, _> = builder.as_ref();
+      if map.get(id).is_none_or(|x| x.is_empty()) {
+        if let Some(value) = fallback.resolve() {
+          builder.add_item(id, value);
+        }
       }
     }
     Ok(builder.collect())
   }
+}
+
+impl HingeConsumer for FallbackNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    match self.child.consume(iterator, context) {
+      Ok(output) => self.fill(output),
+      Err(HingeError::Multiple { errors, partial: Some(partial) }) => Err(HingeError::multiple(errors, Some(self.fill(*partial)?))),
+      Err(error) => Err(error)
+    }
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    self.child.apply_help_info(help);
+    for (id, fallback) in self.fallbacks.iter() {
+      if let Some(child) = help.find_child_mut(id) {
+        if let Some(env) = &fallback.env {
+          child.set_env(env);
+        }
+        if let Some(default) = &fallback.default {
+          child.set_default_value(default);
+        }
+      }
+    }
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChoiceNode {
+  child: Arc<Box<dyn HingeConsumer>>,
+  values: Vec<String>
+}
+
+impl ChoiceNode {
+  pub fn new(child: impl HingeConsumer + 'static, values: Vec<String>) -> Self {
+    ChoiceNode { child: Arc::new(Box::new(child)), values }
+  }
+}
+
+impl HingeConsumer for ChoiceNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    let start = match iterator.next() {
+      Some(token) => {
+        let named = self.child.known_names().iter().any(|x| token == x.as_str());
+        let start = token.index() + usize::from(named);
+        prepend(iterator, vec![token]);
+        start
+      },
+      None => 0
+    };
+    let output = self.child.consume(iterator, context)?;
+    let values: Vec<&str> = match &output {
+      HingeOutput::Value(value) => vec![value],
+      HingeOutput::List(list) => list.iter().filter_map(|x| match x {
+        HingeOutput::Value(value) => Some(value.as_str()),
+        _ => None
+      }).collect(),
+      _ => Vec::new()
+    };
+    for (offset, value) in values.into_iter().enumerate() {
+      if !self.values.iter().any(|x| x == value) {
+        let error = HingeError::new(
+          HingeErrorKind::InvalidValue,
+          format!("invalid value `{}`, possible values: {}", value, self.values.join(", "))
+        ).at(&Token::new(value, start + offset));
+        return Err(match suggest(value, self.values.iter().map(|x| x.as_str())) {
+          Some(suggestion) => error.with_suggestion(suggestion),
+          None => error
+        })
+      }
+    }
+    Ok(output)
+  }
 
   fn apply_help_info(&self, help: &mut HingeHelp) {
     self.child.apply_help_info(help);
+    help.set_possible_values(&self.values);
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
   }
 }
 
 #[derive(Debug, Clone)]
 pub struct KeyWrapNode {
   key: String,
-  wrapped: Rc<Box<dyn HingeConsumer>>
+  wrapped: Arc<Box<dyn HingeConsumer>>
 }
 
 impl KeyWrapNode {
   pub fn new(key: impl AsRef<str>, wrapped: impl HingeConsumer + 'static) -> Self {
-    KeyWrapNode { key: key.as_ref().to_string(), wrapped: Rc::new(Box::new(wrapped)) }
+    KeyWrapNode { key: key.as_ref().to_string(), wrapped: Arc::new(Box::new(wrapped)) }
   }
 }
 
 impl HingeConsumer for KeyWrapNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
-    match self.wrapped.consume(iterator) {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    match self.wrapped.consume(iterator, context) {
       res @ Ok(HingeOutput::Empty) | res @ Err(_) => res,
       Ok(value) => Ok(HingeOutput::Map(
         collections::HashMap::from_iter(iter::once((self.key.clone(), value)))
@@ -290,10 +1282,14 @@ impl HingeConsumer for KeyWrapNode {
   fn apply_help_info(&self, help: &mut HingeHelp) {
     self.wrapped.apply_help_info(help);
   }
+
+  fn known_names(&self) -> Vec<String> {
+    self.wrapped.known_names()
+  }
 }
 
-#[derive(Debug, Clone)]
-pub struct OrNode(Vec<Rc<Box<dyn HingeConsumer>>>);
+#[derive(Debug, Clone, Default)]
+pub struct OrNode(Vec<Arc<Box<dyn HingeConsumer>>>);
 
 impl OrNode {
   pub fn new() -> Self {
@@ -301,7 +1297,7 @@ impl OrNode {
   }
 
   pub fn put(&mut self, value: impl HingeConsumer + 'static) {
-    self.0.push(Rc::new(Box::new(value)));
+    self.0.push(Arc::new(Box::new(value)));
   }
   
   pub fn or(mut self, value: impl HingeConsumer + 'static) -> Self {
@@ -312,12 +1308,16 @@ impl OrNode {
   pub fn len(&self) -> usize {
     self.0.len()
   }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
 }
 
 impl HingeConsumer for OrNode {
-  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>) -> Result<HingeOutput> {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
     let mut options = self.0.iter();
-    while let Some(output) = options.next().map(|x| x.consume(iterator)) {
+    while let Some(output) = options.next().map(|x| x.consume(iterator, context)) {
       if !matches!(output, Ok(HingeOutput::Empty)) {
         return output;
       }
@@ -330,4 +1330,274 @@ impl HingeConsumer for OrNode {
       item.apply_help_info(help.get_new_child())
     }
   }
-}
\ No newline at end of file
+
+  fn known_names(&self) -> Vec<String> {
+    self.0.iter().flat_map(|item| item.known_names()).collect()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainNode {
+  child: Arc<Box<dyn HingeConsumer>>,
+  separator: Option<String>
+}
+
+impl ChainNode {
+  pub fn new(child: impl HingeConsumer + 'static, separator: Option<impl AsRef<str>>) -> Self {
+    ChainNode {
+      child: Arc::new(Box::new(child)),
+      separator: separator.map(|x| x.as_ref().to_string())
+    }
+  }
+
+  fn consume_segment(&self, separator: &str, after_separator: bool, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<(HingeOutput, bool)> {
+    let mut segment = Vec::new();
+    let mut separated = None;
+    for token in iterator.by_ref() {
+      if token == separator {
+        separated = Some(token);
+        break;
+      }
+      segment.push(token);
+    }
+    if after_separator && segment.is_empty() && separated.is_none() {
+      return Err(HingeError::new(HingeErrorKind::MissingRequired, format!("expecting a subcommand after: {}", separator)))
+    }
+    let next = separated.is_some();
+    let mut tokens: Box<dyn Iterator<Item = Token>> = Box::new(segment.clone().into_iter());
+    let output = self.child.consume(&mut tokens, context)?;
+    if output.is_empty() {
+      segment.extend(separated);
+      prepend(iterator, segment);
+    } else if let Some(token) = tokens.next() {
+      return Err(context.unprocessed(&token))
+    }
+    Ok((output, next))
+  }
+}
+
+impl HingeConsumer for ChainNode {
+  fn consume(&self, iterator: &mut Box<dyn Iterator<Item = Token>>, context: &mut ParseContext) -> Result<HingeOutput> {
+    let mut commands = Vec::new();
+    let mut separated = false;
+    loop {
+      let output = match &self.separator {
+        Some(separator) => {
+          let (output, next) = self.consume_segment(separator, separated, iterator, context)?;
+          separated = next;
+          output
+        },
+        None => self.child.consume(iterator, context)?
+      };
+      if output.is_empty() {
+        break;
+      }
+      commands.push(output);
+    }
+    if commands.is_empty() {
+      Ok(HingeOutput::Empty)
+    } else {
+      Ok(HingeOutput::List(commands))
+    }
+  }
+
+  fn apply_help_info(&self, help: &mut HingeHelp) {
+    help.set_repeated(true);
+    self.child.apply_help_info(help);
+  }
+
+  fn known_names(&self) -> Vec<String> {
+    self.child.known_names()
+  }
+}
+#[cfg(test)]
+mod tests {
+  use crate::{HingeBuilder, HingeErrorKind};
+  use crate::testing::{parse, values};
+
+  #[test]
+  fn terminator_fills_positionals_and_records_tail() {
+    let hinge = HingeBuilder::new().bool("v", 'v').arg("a").arg("b").catch_tail("rest").build();
+    let output = parse(&hinge, &["--", "-v", "y", "z"]).unwrap();
+    assert!(output.get_item("v").unwrap().is_empty());
+    assert!(output.get_item("a").unwrap().is_value(Some("-v")));
+    assert!(output.get_item("b").unwrap().is_value(Some("y")));
+    assert_eq!(values(output.get_item("rest").unwrap()), vec!["-v", "y", "z"]);
+  }
+
+  #[test]
+  fn positionals_do_not_take_options_before_the_terminator() {
+    let hinge = HingeBuilder::new().bool("v", 'v').arg("a").arg("b").build();
+    let output = parse(&hinge, &["x", "-v", "y"]).unwrap();
+    assert!(output.get_item("v").unwrap().is_true());
+    assert!(output.get_item("a").unwrap().is_value(Some("x")));
+    assert!(output.get_item("b").unwrap().is_value(Some("y")));
+    assert_eq!(parse(&hinge, &["x", "-w"]).unwrap_err().kind(), Some(HingeErrorKind::UnknownArgument));
+  }
+
+  #[test]
+  fn terminator_without_tail_only_disables_options() {
+    let hinge = HingeBuilder::new().bool("v", 'v').arg("a").build();
+    let output = parse(&hinge, &["--", "-v"]).unwrap();
+    assert!(output.get_item("v").unwrap().is_empty());
+    assert!(output.get_item("a").unwrap().is_value(Some("-v")));
+  }
+
+  #[test]
+  fn unknown_option_takes_priority_over_missing_required() {
+    let hinge = HingeBuilder::new().bool("verbose", "verbose").item("cfg", "config").require().build();
+    let error = parse(&hinge, &["--verbos", "--config", "x"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::UnknownArgument));
+    assert_eq!(error.parse_error().and_then(|x| x.token()), Some("--verbos"));
+    assert_eq!(error.parse_error().and_then(|x| x.suggestion()), Some("--verbose"));
+  }
+
+  #[test]
+  fn unknown_option_takes_priority_over_missing_subcommand() {
+    let hinge = HingeBuilder::new()
+      .subcommand("run", "run", HingeBuilder::new())
+      .require_subcommand()
+      .build();
+    let error = parse(&hinge, &["--nope", "run"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::UnknownArgument));
+  }
+
+  #[test]
+  fn conflicting_options_underline_both_tokens() {
+    let hinge = HingeBuilder::new().bool("json", "json").conflicts_with("plain").bool("plain", "plain").build();
+    let error = parse(&hinge, &["--json", "--plain"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::Conflict));
+    assert_eq!(error.parse_error().map(|x| x.related_positions()), Some(&[0][..]));
+    assert_eq!(error.render(&["--json", "--plain"]).lines().last(), Some("  ^^^^^^ ^^^^^^^"));
+    assert!(parse(&hinge, &["--plain"]).is_ok());
+  }
+
+  #[test]
+  fn deprecated_options_report_through_the_parse_context() {
+    let hinge = HingeBuilder::new()
+      .subcommand("run", "run", HingeBuilder::new().bool("old", "old").deprecated_for("--new").bool("new", "new"))
+      .build();
+    let tokens = vec!["run".to_string(), "--old".to_string()];
+    let (result, diagnostics) = hinge.apply_tokens_with_diagnostics(tokens.into_iter());
+    assert!(result.unwrap().get_item("run").unwrap().get_item("old").unwrap().is_true());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message(), "--old is deprecated, use --new instead");
+    assert_eq!(diagnostics[0].position(), Some(1));
+    let (_, diagnostics) = hinge.apply_tokens_with_diagnostics(vec!["run".to_string()].into_iter());
+    assert!(diagnostics.is_empty());
+  }
+
+  #[test]
+  fn collect_errors_reports_every_problem() {
+    let hinge = HingeBuilder::new()
+      .item("a", "a").require()
+      .item("b", "b").require()
+      .item("n", "n").possible_values(["x"])
+      .collect_errors()
+      .build();
+    let error = parse(&hinge, &["--n", "y", "--zzz"]).unwrap_err();
+    let kinds: Vec<_> = error.errors().into_iter().map(|x| x.kind().unwrap()).collect();
+    assert_eq!(kinds, vec![
+      HingeErrorKind::InvalidValue,
+      HingeErrorKind::UnknownArgument,
+      HingeErrorKind::MissingRequired,
+      HingeErrorKind::MissingRequired
+    ]);
+    let ids: Vec<_> = error.errors().into_iter().filter_map(|x| x.parse_error().and_then(|x| x.id())).collect();
+    assert_eq!(ids, vec!["n", "a", "b"]);
+    assert!(error.partial_output().is_some());
+  }
+
+  #[test]
+  fn fail_fast_reports_the_first_problem() {
+    let hinge = HingeBuilder::new().item("b", "b").require().item("a", "a").require().build();
+    let error = parse(&hinge, &[]).unwrap_err();
+    assert_eq!(error.errors().len(), 1);
+    assert_eq!(error.kind(), Some(HingeErrorKind::MissingRequired));
+    assert_eq!(error.parse_error().and_then(|x| x.id()), Some("b"));
+  }
+
+  #[test]
+  fn stop_at_operand_passes_the_rest_through() {
+    let hinge = HingeBuilder::new().bool("v", 'v').stop_at_operand("cmd").build();
+    let output = parse(&hinge, &["-v", "ls", "-v", "--", "x"]).unwrap();
+    assert!(output.get_item("v").unwrap().is_true());
+    assert_eq!(values(output.get_item("cmd").unwrap()), vec!["ls", "-v", "--", "x"]);
+    let output = parse(&hinge, &["--", "-v"]).unwrap();
+    assert!(output.get_item("v").unwrap().is_empty());
+    assert_eq!(values(output.get_item("cmd").unwrap()), vec!["-v"]);
+    assert!(parse(&hinge, &["-v"]).unwrap().get_item("cmd").unwrap().is_empty());
+  }
+
+  #[test]
+  fn unique_prefixes_expand_and_exact_names_win() {
+    let hinge = HingeBuilder::new()
+      .bool("verbose", "verbose")
+      .bool("version-check", "version-check")
+      .bool("ver", "ver")
+      .abbreviate_long_options()
+      .build();
+    assert!(parse(&hinge, &["--verb"]).unwrap().get_item("verbose").unwrap().is_true());
+    assert!(parse(&hinge, &["--ver"]).unwrap().get_item("ver").unwrap().is_true());
+    assert!(parse(&hinge, &["--vers"]).unwrap().get_item("version-check").unwrap().is_true());
+    let error = parse(&hinge, &["--v"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::UnknownArgument));
+    assert_eq!(error.to_string(), "ambiguous option --v, could be: --verbose, --version-check, --ver");
+  }
+
+  #[test]
+  fn abbreviation_is_opt_in() {
+    let hinge = HingeBuilder::new().bool("verbose", "verbose").build();
+    assert_eq!(parse(&hinge, &["--verb"]).unwrap_err().kind(), Some(HingeErrorKind::UnknownArgument));
+  }
+
+  #[test]
+  fn chained_subcommands_keep_their_order() {
+    let hinge = HingeBuilder::new()
+      .chain_subcommands()
+      .subcommand("clean", "clean", HingeBuilder::new().bool("all", "all"))
+      .subcommand("build", "build", HingeBuilder::new())
+      .build();
+    let output = parse(&hinge, &["clean", "--all", "build", "clean"]).unwrap();
+    let commands = output.get_commands().unwrap();
+    let ids: Vec<_> = commands.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec!["clean", "build", "clean"]);
+    assert!(commands[0].1.get_item("all").unwrap().is_true());
+    assert!(commands[2].1.get_item("all").unwrap().is_empty());
+  }
+
+  #[test]
+  fn chain_separator_splits_segments() {
+    let hinge = HingeBuilder::new()
+      .chain_separator("+")
+      .subcommand("clean", "clean", HingeBuilder::new().list("files", "files", None))
+      .subcommand("build", "build", HingeBuilder::new().arg("target"))
+      .build();
+    let output = parse(&hinge, &["clean", "--files", "a", "b", "+", "build", "x"]).unwrap();
+    let commands = output.get_commands().unwrap();
+    assert_eq!(commands[0].0, "clean");
+    assert_eq!(values(commands[0].1.get_item("files").unwrap()), vec!["a", "b"]);
+    assert_eq!(commands[1].0, "build");
+    assert!(commands[1].1.get_item("target").unwrap().is_value(Some("x")));
+    let error = parse(&hinge, &["build", "x", "y", "+", "clean"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::TooManyValues));
+    assert_eq!(error.parse_error().and_then(|x| x.token()), Some("y"));
+    let error = parse(&hinge, &["build", "x", "+"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::MissingRequired));
+    assert_eq!(error.to_string(), "expecting a subcommand after: +");
+  }
+
+  #[test]
+  fn extra_values_are_too_many_values() {
+    let hinge = HingeBuilder::new().bool("v", 'v').arg("a").arg("b").build();
+    let error = parse(&hinge, &["x", "y", "z"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::TooManyValues));
+    let error = error.parse_error().unwrap();
+    assert_eq!((error.id(), error.token(), error.position()), (Some("b"), Some("z"), Some(2)));
+    let hinge = HingeBuilder::new().bool("v", 'v').list("pair", "pair", Some(2)).build();
+    let error = parse(&hinge, &["--pair", "a", "b", "c"]).unwrap_err();
+    assert_eq!(error.kind(), Some(HingeErrorKind::TooManyValues));
+    assert_eq!(error.parse_error().and_then(|x| x.id()), Some("pair"));
+    assert_eq!(parse(&hinge, &["c"]).unwrap_err().kind(), Some(HingeErrorKind::UnexpectedToken));
+  }
+}
@@ -0,0 +1,109 @@
+use core::fmt;
+
+use crate::{ColorChoice, HingeError, HingeErrorKind, Token};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+  message: String,
+  token: Option<String>,
+  position: Option<usize>
+}
+
+impl Diagnostic {
+  pub fn new(message: impl AsRef<str>) -> Self {
+    Diagnostic { message: message.as_ref().to_string(), token: None, position: None }
+  }
+
+  pub fn at(mut self, token: &Token) -> Self {
+    self.token = Some(token.to_string());
+    self.position = Some(token.index());
+    self
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  pub fn token(&self) -> Option<&str> {
+    self.token.as_deref()
+  }
+
+  pub fn position(&self) -> Option<usize> {
+    self.position
+  }
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "warning: {}", self.message)
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseContext {
+  diagnostics: Vec<Diagnostic>,
+  color: Option<ColorChoice>,
+  help_json_flag: Vec<String>,
+  surplus: Vec<(usize, Option<String>)>
+}
+
+impl ParseContext {
+  pub fn new() -> Self {
+    ParseContext { diagnostics: Vec::new(), color: None, help_json_flag: Vec::new(), surplus: Vec::new() }
+  }
+
+  pub fn set_color(&mut self, color: ColorChoice) {
+    self.color = Some(color);
+  }
+
+  pub fn color(&self) -> Option<ColorChoice> {
+    self.color
+  }
+
+  pub fn inherit_help_json_flag(&mut self, names: &[String]) {
+    if self.help_json_flag.is_empty() {
+      self.help_json_flag = names.to_vec();
+    }
+  }
+
+  pub fn is_help_json_flag(&self, token: &Token) -> bool {
+    self.help_json_flag.iter().any(|x| **token == *x)
+  }
+
+  pub fn report(&mut self, diagnostic: Diagnostic) {
+    self.diagnostics.push(diagnostic);
+  }
+
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self.diagnostics
+  }
+
+  pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+    self.diagnostics
+  }
+
+  pub fn mark_surplus(&mut self, token: &Token, id: Option<&str>) {
+    match self.surplus.iter_mut().find(|x| x.0 == token.index()) {
+      Some((_, known @ None)) => *known = id.map(String::from),
+      Some(_) => (),
+      None => self.surplus.push((token.index(), id.map(String::from)))
+    }
+  }
+
+  pub fn is_surplus(&self, token: &Token) -> bool {
+    self.surplus.iter().any(|x| x.0 == token.index())
+  }
+
+  pub fn unprocessed(&self, token: &Token) -> HingeError {
+    match self.surplus.iter().find(|x| x.0 == token.index()) {
+      Some((_, id)) => {
+        let error = HingeError::new(HingeErrorKind::TooManyValues, format!("too many values, next is: {}", token)).at(token);
+        match id {
+          Some(id) => error.with_id(id),
+          None => error
+        }
+      },
+      None => HingeError::unprocessed(token)
+    }
+  }
+}
@@ -1,17 +1,324 @@
 use core::fmt;
-use std::{result, error::Error, rc::Rc};
+use std::{result, error::Error, sync::Arc, io, num::{ParseIntError, ParseFloatError}};
+
+use crate::{Token, HingeOutput, Theme, display_width};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HingeErrorKind {
+  UnknownArgument,
+  MissingValue,
+  MissingRequired,
+  InvalidValue,
+  TooFewValues,
+  TooManyValues,
+  Conflict,
+  UnexpectedToken,
+  ValidationFailed,
+  DisplayHelp,
+  DisplayHelpJson,
+  DisplayVersion,
+  ShortCircuit
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+  kind: HingeErrorKind,
+  message: String,
+  id: Option<String>,
+  token: Option<String>,
+  position: Option<usize>,
+  related: Vec<usize>,
+  suggestion: Option<String>,
+  path: Vec<String>
+}
+
+impl ParseError {
+  pub fn kind(&self) -> HingeErrorKind {
+    self.kind
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  pub fn id(&self) -> Option<&str> {
+    self.id.as_deref()
+  }
+
+  pub fn token(&self) -> Option<&str> {
+    self.token.as_deref()
+  }
+
+  pub fn position(&self) -> Option<usize> {
+    self.position
+  }
+
+  pub fn related_positions(&self) -> &[usize] {
+    &self.related
+  }
+
+  pub fn suggestion(&self) -> Option<&str> {
+    self.suggestion.as_deref()
+  }
+
+  pub fn path(&self) -> &[String] {
+    &self.path
+  }
+
+  fn marks(&self, offsets: &[(usize, usize)], end: usize) -> Vec<(usize, usize)> {
+    let mark = |position: usize, after: bool| match offsets.get(position) {
+      Some(&(offset, width)) if after => (offset + width + 1, 1),
+      Some(&(offset, width)) => (offset, width.max(1)),
+      None => (end + 1, 1)
+    };
+    let after = matches!(self.kind, HingeErrorKind::MissingValue | HingeErrorKind::TooFewValues);
+    let mut marks: Vec<_> = self.position.map(|x| mark(x, after)).into_iter()
+      .chain(self.related.iter().map(|x| mark(*x, false)))
+      .collect();
+    marks.sort();
+    marks
+  }
+}
 
 #[derive(Debug, Clone)]
 pub enum HingeError {
-  Wrapper(Rc<&'static dyn Error>),
-  String(String)
+  Wrapper(Arc<dyn Error + Send + Sync>),
+  String(String),
+  Context {
+    context: String,
+    source: Box<HingeError>
+  },
+  Parse(Box<ParseError>),
+  Multiple {
+    errors: Vec<HingeError>,
+    partial: Option<Box<HingeOutput>>
+  }
+}
+
+impl HingeError {
+  pub fn new(kind: HingeErrorKind, message: impl AsRef<str>) -> Self {
+    HingeError::Parse(Box::new(ParseError {
+      kind,
+      message: message.as_ref().to_string(),
+      id: None,
+      token: None,
+      position: None,
+      related: Vec::new(),
+      suggestion: None,
+      path: Vec::new()
+    }))
+  }
+
+  pub fn wrap(error: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+    HingeError::Wrapper(Arc::from(error.into()))
+  }
+
+  pub fn context(self, context: impl AsRef<str>) -> Self {
+    match self {
+      Self::Multiple { errors, partial } => Self::Multiple {
+        errors: errors.into_iter().map(|error| error.context(context.as_ref())).collect(),
+        partial
+      },
+      error => Self::Context { context: context.as_ref().to_string(), source: Box::new(error) }
+    }
+  }
+
+  pub fn multiple(errors: Vec<HingeError>, partial: Option<HingeOutput>) -> Self {
+    let errors = errors.into_iter().flat_map(|error| match error {
+      Self::Multiple { errors, .. } => errors,
+      error => vec![error]
+    }).collect();
+    HingeError::Multiple { errors, partial: partial.map(Box::new) }
+  }
+
+  pub fn map(self, f: impl Fn(HingeError) -> HingeError) -> Self {
+    match self {
+      Self::Multiple { errors, partial } => Self::Multiple { errors: errors.into_iter().map(f).collect(), partial },
+      error => f(error)
+    }
+  }
+
+  pub fn errors(&self) -> Vec<&HingeError> {
+    match self {
+      Self::Multiple { errors, .. } => errors.iter().collect(),
+      error => vec![error]
+    }
+  }
+
+  pub fn partial_output(&self) -> Option<&HingeOutput> {
+    match self {
+      Self::Multiple { partial, .. } => partial.as_deref(),
+      _ => None
+    }
+  }
+
+  pub fn unprocessed(token: &Token) -> Self {
+    let kind = match token.starts_with('-') && *token != "-" {
+      true => HingeErrorKind::UnknownArgument,
+      false => HingeErrorKind::UnexpectedToken
+    };
+    HingeError::new(kind, format!("not every token could be processed, next is: {}", token)).at(token)
+  }
+
+  pub fn kind(&self) -> Option<HingeErrorKind> {
+    self.parse_error().map(|x| x.kind)
+  }
+
+  pub fn parse_error(&self) -> Option<&ParseError> {
+    match self {
+      Self::Parse(error) => Some(error.as_ref()),
+      Self::Context { source, .. } => source.parse_error(),
+      _ => None
+    }
+  }
+
+  fn parse_error_mut(&mut self) -> Option<&mut ParseError> {
+    match self {
+      Self::Parse(error) => Some(error.as_mut()),
+      Self::Context { source, .. } => source.parse_error_mut(),
+      _ => None
+    }
+  }
+
+  pub fn with_message(mut self, message: impl AsRef<str>) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.message = message.as_ref().to_string();
+    }
+    self
+  }
+
+  pub fn with_path(mut self, path: Vec<String>) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.path = path;
+    }
+    self
+  }
+
+  pub fn within(mut self, command: impl AsRef<str>) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.path.insert(0, command.as_ref().to_string());
+    }
+    self
+  }
+
+  pub fn with_id(mut self, id: impl AsRef<str>) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.id = Some(id.as_ref().to_string());
+    }
+    self
+  }
+
+  pub fn with_token(mut self, token: impl AsRef<str>) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.token = Some(token.as_ref().to_string());
+    }
+    self
+  }
+
+  pub fn with_position(mut self, position: usize) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.position = Some(position);
+    }
+    self
+  }
+
+  pub fn with_related(mut self, position: usize) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.related.push(position);
+    }
+    self
+  }
+
+  pub fn with_suggestion(mut self, suggestion: impl AsRef<str>) -> Self {
+    if let Some(error) = self.parse_error_mut() {
+      error.suggestion = Some(suggestion.as_ref().to_string());
+    }
+    self
+  }
+
+  pub fn at(self, token: &Token) -> Self {
+    self.with_token(&**token).with_position(token.index())
+  }
+
+  pub fn render(&self, tokens: &[impl AsRef<str>]) -> String {
+    self.render_with(tokens, &Theme::plain())
+  }
+
+  pub fn render_with(&self, tokens: &[impl AsRef<str>], theme: &Theme) -> String {
+    if let Self::Multiple { errors, .. } = self {
+      return errors.iter().map(|error| error.render_with(tokens, theme)).collect::<Vec<_>>().join("\n")
+    }
+    let label = theme.get_error().paint("error:");
+    let mut line = String::new();
+    let mut offsets = Vec::new();
+    for token in tokens {
+      if !line.is_empty() {
+        line.push(' ');
+      }
+      let width = display_width(token);
+      offsets.push((display_width(&line), width));
+      line.push_str(token.as_ref());
+    }
+    let marks = match self.parse_error() {
+      Some(error) => error.marks(&offsets, display_width(&line)),
+      None => Vec::new()
+    };
+    if marks.is_empty() {
+      return format!("{} {:#}", label, self)
+    }
+    let mut carets = String::new();
+    for (offset, width) in marks {
+      let column = display_width(&carets);
+      if offset > column {
+        carets.push_str(&" ".repeat(offset - column));
+      }
+      let covered = display_width(&carets) - offset;
+      carets.push_str(&"^".repeat(width.saturating_sub(covered)));
+    }
+    format!("{} {:#}\n  {}\n  {}", label, self, line, theme.get_error().paint(carets))
+  }
+
+  pub fn or_id(self, id: impl AsRef<str>) -> Self {
+    match self.parse_error().and_then(|x| x.id.as_ref()) {
+      Some(_) => self,
+      None => self.with_id(id)
+    }
+  }
+
+  pub fn or_at(self, token: &Token) -> Self {
+    match self.parse_error().and_then(|x| x.token.as_ref()) {
+      Some(_) => self,
+      None => self.at(token)
+    }
+  }
 }
 
 impl fmt::Display for HingeError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
-      Self::Wrapper(wrapped) => wrapped.fmt(f),
-      Self::String(string) => string.fmt(f)
+      Self::Wrapper(wrapped) => {
+        wrapped.fmt(f)?;
+        let mut current = wrapped.source().filter(|_| f.alternate());
+        while let Some(error) = current {
+          write!(f, ": {}", error)?;
+          current = error.source();
+        }
+        Ok(())
+      },
+      Self::String(string) => string.fmt(f),
+      Self::Context { context, source } if f.alternate() => write!(f, "{}: {:#}", context, source),
+      Self::Context { context, .. } => context.fmt(f),
+      Self::Parse(error) => match &error.suggestion {
+        Some(suggestion) => write!(f, "{}, did you mean {}?", error.message, suggestion),
+        None => error.message.fmt(f)
+      },
+      Self::Multiple { errors, .. } => {
+        let messages: Vec<_> = errors.iter().map(|error| match f.alternate() {
+          true => format!("{:#}", error),
+          false => error.to_string()
+        }).collect();
+        messages.join("\n").fmt(f)
+      }
     }
   }
 }
@@ -19,16 +326,98 @@ impl fmt::Display for HingeError {
 impl Error for HingeError {
   fn source(&self) -> Option<&(dyn Error + 'static)> {
     match self {
-      Self::Wrapper(wrapped) => Some(wrapped.as_ref()),
+      Self::Wrapper(wrapped) => wrapped.source(),
+      Self::Context { source, .. } => Some(source.as_ref()),
       _ => None
     }
   }
 }
 
-impl Into<HingeError> for String {
-  fn into(self) -> HingeError {
-    return HingeError::String(self)
+impl From<String> for HingeError {
+  fn from(value: String) -> Self {
+    HingeError::String(value)
   }
 }
 
-pub type Result<T> = result::Result<T, HingeError>;
\ No newline at end of file
+impl From<Box<dyn Error + Send + Sync>> for HingeError {
+  fn from(value: Box<dyn Error + Send + Sync>) -> Self {
+    HingeError::Wrapper(Arc::from(value))
+  }
+}
+
+impl From<io::Error> for HingeError {
+  fn from(value: io::Error) -> Self {
+    HingeError::wrap(value)
+  }
+}
+
+impl From<ParseIntError> for HingeError {
+  fn from(value: ParseIntError) -> Self {
+    HingeError::wrap(value)
+  }
+}
+
+impl From<ParseFloatError> for HingeError {
+  fn from(value: ParseFloatError) -> Self {
+    HingeError::wrap(value)
+  }
+}
+
+pub trait ResultContext<T> {
+  fn context(self, context: impl AsRef<str>) -> Result<T>;
+}
+
+impl<T, E: Into<HingeError>> ResultContext<T> for result::Result<T, E> {
+  fn context(self, context: impl AsRef<str>) -> Result<T> {
+    self.map_err(|error| error.into().context(context))
+  }
+}
+
+pub type Result<T> = result::Result<T, HingeError>;
+
+#[cfg(test)]
+mod tests {
+  use std::{error::Error, fmt};
+
+  use super::{HingeError, ResultContext};
+
+  #[derive(Debug)]
+  struct Outer(std::num::ParseIntError);
+
+  impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      write!(f, "bad port")
+    }
+  }
+
+  impl Error for Outer {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+      Some(&self.0)
+    }
+  }
+
+  fn chain(error: &HingeError) -> Vec<String> {
+    let mut messages = vec![error.to_string()];
+    let mut current = error.source();
+    while let Some(error) = current {
+      messages.push(error.to_string());
+      current = error.source();
+    }
+    messages
+  }
+
+  #[test]
+  fn each_context_layer_appears_once() {
+    let error = "x".parse::<u16>().context("in subcommand `run`").context("reading config").unwrap_err();
+    assert_eq!(chain(&error), vec!["reading config", "in subcommand `run`", "invalid digit found in string"]);
+    assert_eq!(format!("{:#}", error), "reading config: in subcommand `run`: invalid digit found in string");
+  }
+
+  #[test]
+  fn wrapper_exposes_only_the_undisplayed_chain() {
+    let inner = "x".parse::<u16>().unwrap_err();
+    let error = HingeError::wrap(Outer(inner)).context("loading");
+    assert_eq!(chain(&error), vec!["loading", "bad port", "invalid digit found in string"]);
+    assert_eq!(format!("{:#}", error), "loading: bad port: invalid digit found in string");
+  }
+}
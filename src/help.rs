@@ -1,27 +1,158 @@
-#[derive(Clone, Debug)]
+use std::{io::{self, Write}, sync::Arc};
+
+use crate::{ColorChoice, Theme, Stream, HelpRenderer, PlainRenderer};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HelpArity {
+  #[default]
+  None,
+  One,
+  Many(Option<usize>)
+}
+
+pub fn display_width(text: impl AsRef<str>) -> usize {
+  let mut escaped = false;
+  text.as_ref().chars().map(|c| match (escaped, c as u32) {
+    (true, _) => {
+      escaped = !c.is_ascii_alphabetic();
+      0
+    },
+    (false, 0x1B) => {
+      escaped = true;
+      0
+    },
+    (false, c) => match c {
+      0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F | 0x20D0..=0x20FF => 0,
+      0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60
+      | 0xFFE0..=0xFFE6 | 0x1F300..=0x1F64F | 0x1F900..=0x1F9FF | 0x20000..=0x3FFFD => 2,
+      _ => 1
+    }
+  }).sum()
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct HingeHelp {
+  program: Option<String>,
+  width: Option<usize>,
+  sorted: bool,
+  color: Option<ColorChoice>,
+  theme: Theme,
+  renderer: Option<Arc<dyn HelpRenderer>>,
+  hidden: bool,
+  global: bool,
+  inherited: bool,
+  heading: Option<String>,
+  id: Option<String>,
   names: Vec<String>,
   alt_name: Option<String>,
   description: Option<String>,
+  long_description: Option<String>,
+  after_help: Option<String>,
+  annotations: Vec<String>,
+  value_name: Option<String>,
+  default_value: Option<String>,
+  env: Option<String>,
+  possible_values: Vec<String>,
+  arity: HelpArity,
+  required: bool,
+  repeated: bool,
   childs: Vec<HingeHelp>,
   tabulate_childs: bool
 }
 
-const TAB: &'static str = "  ";
-
 impl HingeHelp {
   pub const DEFAULT_TABULATE: bool = false;
+  pub const DEFAULT_WIDTH: usize = 80;
 
   pub fn new() -> Self {
     HingeHelp {
+      program: None,
+      width: None,
+      sorted: false,
+      color: None,
+      theme: Theme::new(),
+      renderer: None,
+      hidden: false,
+      global: false,
+      inherited: false,
+      heading: None,
+      id: None,
       names: Vec::new(),
       alt_name: None,
       description: None,
+      long_description: None,
+      after_help: None,
+      annotations: Vec::new(),
+      value_name: None,
+      default_value: None,
+      env: None,
+      possible_values: Vec::new(),
+      arity: HelpArity::None,
+      required: false,
+      repeated: false,
       childs: Vec::new(),
       tabulate_childs: HingeHelp::DEFAULT_TABULATE
     }
   }
 
+  pub fn set_program(&mut self, program: impl AsRef<str>) {
+    self.program = Some(program.as_ref().to_string())
+  }
+
+  pub fn set_width(&mut self, width: usize) {
+    self.width = Some(width)
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+      .or(std::env::var("COLUMNS").ok().and_then(|x| x.trim().parse().ok()))
+      .unwrap_or(HingeHelp::DEFAULT_WIDTH)
+  }
+
+  pub fn set_sorted(&mut self, sorted: bool) {
+    self.sorted = sorted;
+  }
+
+  pub fn set_color(&mut self, color: ColorChoice) {
+    self.color = Some(color);
+  }
+
+  pub fn color(&self) -> Option<ColorChoice> {
+    self.color
+  }
+
+  pub fn set_theme(&mut self, theme: Theme) {
+    self.theme = theme;
+  }
+
+  pub fn set_renderer(&mut self, renderer: Arc<dyn HelpRenderer>) {
+    self.renderer = Some(renderer);
+  }
+
+  pub fn theme_for(&self, stream: Stream) -> Theme {
+    match self.color.unwrap_or(ColorChoice::Never).enabled(stream) {
+      true => self.theme,
+      false => Theme::plain()
+    }
+  }
+
+  pub fn set_hidden(&mut self, hidden: bool) {
+    self.hidden = hidden;
+  }
+
+  pub fn set_global(&mut self, global: bool) {
+    self.global = global;
+  }
+
+  pub fn set_heading(&mut self, heading: impl AsRef<str>) {
+    self.heading = Some(heading.as_ref().to_string())
+  }
+
+  pub fn set_id(&mut self, id: impl AsRef<str>) {
+    self.id = Some(id.as_ref().to_string())
+  }
+
   pub fn add_name(&mut self, name: impl AsRef<str>) {
     self.names.push(name.as_ref().to_string())
   }
@@ -34,34 +165,368 @@ impl HingeHelp {
     self.description = Some(description.as_ref().to_string())
   }
 
+  pub fn set_long_description(&mut self, description: impl AsRef<str>) {
+    self.long_description = Some(description.as_ref().to_string())
+  }
+
+  pub fn set_after_help(&mut self, after_help: impl AsRef<str>) {
+    self.after_help = Some(after_help.as_ref().to_string())
+  }
+
+  pub fn add_annotation(&mut self, annotation: impl AsRef<str>) {
+    self.annotations.push(annotation.as_ref().to_string())
+  }
+
+  pub fn set_value_name(&mut self, value_name: impl AsRef<str>) {
+    self.value_name = Some(value_name.as_ref().to_string())
+  }
+
+  pub fn set_default_value(&mut self, default_value: impl AsRef<str>) {
+    self.default_value = Some(default_value.as_ref().to_string())
+  }
+
+  pub fn set_env(&mut self, env: impl AsRef<str>) {
+    self.env = Some(env.as_ref().to_string())
+  }
+
+  pub fn set_possible_values(&mut self, values: impl IntoIterator<Item = impl AsRef<str>>) {
+    self.possible_values = values.into_iter().map(|x| x.as_ref().to_string()).collect()
+  }
+
+  pub fn set_arity(&mut self, arity: HelpArity) {
+    self.arity = arity;
+  }
+
+  pub fn set_required(&mut self, required: bool) {
+    self.required = required;
+  }
+
+  pub fn set_repeated(&mut self, repeated: bool) {
+    self.repeated = repeated;
+  }
+
   pub fn get_new_child(&mut self) -> &mut Self {
     self.childs.push(HingeHelp::new());
     self.childs.last_mut().unwrap()
   }
 
+  pub fn childs_mut(&mut self) -> &mut [HingeHelp] {
+    &mut self.childs
+  }
+
+  pub fn find_child_mut(&mut self, id: impl AsRef<str>) -> Option<&mut Self> {
+    self.childs.iter_mut().find(|x| x.id.as_deref() == Some(id.as_ref()))
+  }
+
   pub fn set_tabulate_childs(&mut self, tabulate: bool) {
     self.tabulate_childs = tabulate;
   }
 
-  pub fn generate(&self) -> String {
-    let header = match self.names.join(", ") {
-      names if names.len() > 0 => names,
-      _ => self.alt_name.clone().unwrap_or_default()
-    } + self.description.as_ref().unwrap_or(&String::new());
-    
-    let body = self.childs.iter().map(|c| {
-      if self.tabulate_childs {
-        c.generate().split("\n").map(|x| String::from(TAB) + &x).collect::<Vec<String>>()
-      } else {
-        c.generate().split("\n").map(|x| x.to_string()).collect::<Vec<String>>()
+  pub fn program(&self) -> Option<&str> {
+    self.program.as_deref()
+  }
+
+  pub fn id(&self) -> Option<&str> {
+    self.id.as_deref()
+  }
+
+  pub fn names(&self) -> &[String] {
+    &self.names
+  }
+
+  pub fn alternative_name(&self) -> Option<&str> {
+    self.alt_name.as_deref()
+  }
+
+  pub fn description(&self) -> Option<&str> {
+    self.description.as_deref()
+  }
+
+  pub fn long_description(&self) -> Option<&str> {
+    self.long_description.as_deref()
+  }
+
+  pub fn after_help(&self) -> Option<&str> {
+    self.after_help.as_deref()
+  }
+
+  pub fn annotations(&self) -> &[String] {
+    &self.annotations
+  }
+
+  pub fn value_name(&self) -> Option<&str> {
+    self.value_name.as_deref()
+  }
+
+  pub fn default_value(&self) -> Option<&str> {
+    self.default_value.as_deref()
+  }
+
+  pub fn env(&self) -> Option<&str> {
+    self.env.as_deref()
+  }
+
+  pub fn possible_values(&self) -> &[String] {
+    &self.possible_values
+  }
+
+  pub fn arity(&self) -> HelpArity {
+    self.arity
+  }
+
+  pub fn is_required(&self) -> bool {
+    self.required
+  }
+
+  pub fn is_repeated(&self) -> bool {
+    self.repeated
+  }
+
+  pub fn is_hidden(&self) -> bool {
+    self.hidden
+  }
+
+  pub fn is_global(&self) -> bool {
+    self.global
+  }
+
+  pub fn is_inherited(&self) -> bool {
+    self.inherited
+  }
+
+  pub fn is_sorted(&self) -> bool {
+    self.sorted
+  }
+
+  pub fn heading(&self) -> Option<&str> {
+    self.heading.as_deref()
+  }
+
+  pub fn childs(&self) -> &[HingeHelp] {
+    &self.childs
+  }
+
+  pub fn tabulate_childs(&self) -> bool {
+    self.tabulate_childs
+  }
+
+  pub fn collect_names(&self) -> Vec<&str> {
+    self.names.iter().map(|x| x.as_str())
+      .chain(self.childs.iter().flat_map(|x| x.collect_names()))
+      .collect()
+  }
+
+  pub fn is_option(&self) -> bool {
+    self.names.first().is_some_and(|x| x.starts_with('-') && x != "--")
+  }
+
+  pub fn is_command(&self) -> bool {
+    self.names.first().is_some_and(|x| !x.starts_with('-'))
+  }
+
+  pub fn is_tail(&self) -> bool {
+    self.names.first().is_some_and(|x| x == "--")
+  }
+
+  pub fn is_positional(&self) -> bool {
+    self.names.is_empty() && self.childs.is_empty() && (self.arity != HelpArity::None || self.repeated)
+  }
+
+  pub fn placeholder(&self) -> String {
+    let name = self.value_name.clone()
+      .or(self.id.as_ref().map(|x| x.to_uppercase()))
+      .unwrap_or(String::from("VALUE"));
+    match self.arity {
+      HelpArity::Many(_) => format!("<{}>...", name),
+      _ => format!("<{}>", name)
+    }
+  }
+
+  fn usage_parts(&self, parts: &mut Vec<String>, optional_options: &mut bool, command: &mut Option<(bool, bool)>) {
+    for child in self.childs.iter().filter(|x| !x.hidden && !x.inherited) {
+      if child.is_option() {
+        let name = child.names.last().unwrap();
+        match (child.required, child.arity) {
+          (true, HelpArity::None) => parts.push(name.clone()),
+          (true, _) => parts.push(format!("{} {}", name, child.placeholder())),
+          (false, _) => *optional_options = true
+        }
+      } else if child.is_command() {
+        let (required, repeated) = command.unwrap_or_default();
+        *command = Some((required, repeated || self.repeated));
+      } else if child.is_tail() {
+        parts.push(format!("[-- <{}>...]", child.id.as_deref().unwrap_or("ARGS")));
+      } else if child.is_positional() {
+        let name = child.value_name.clone().or(child.id.clone()).unwrap_or(String::from("ARGS"));
+        let repeated = match child.arity {
+          HelpArity::Many(_) => "...",
+          _ if child.repeated => "...",
+          _ => ""
+        };
+        match child.required {
+          true => parts.push(format!("<{}>{}", name, repeated)),
+          false => parts.push(format!("[{}]{}", name, repeated))
+        }
+      } else if child.names.is_empty() && !child.childs.is_empty() {
+        child.usage_parts(parts, optional_options, command);
+      } else if child.names.is_empty() && child.required {
+        let (_, repeated) = command.unwrap_or_default();
+        *command = Some((true, repeated));
       }
-    }).flatten().reduce(|a, b| format!("{}\n{}", a, b)).unwrap_or_default();
+    }
+  }
+
+  fn program_name(&self) -> String {
+    self.program.clone().unwrap_or_else(|| {
+      std::env::args().next()
+        .and_then(|x| std::path::Path::new(&x).file_name().map(|x| x.to_string_lossy().to_string()))
+        .unwrap_or_default()
+    })
+  }
+
+  fn commands(&self) -> Vec<&HingeHelp> {
+    self.childs.iter().flat_map(|child| match (child.is_command(), child.names.is_empty()) {
+      (true, _) => vec![child],
+      (false, true) => child.commands(),
+      (false, false) => Vec::new()
+    }).collect()
+  }
+
+  fn globals(&self) -> Vec<&HingeHelp> {
+    self.childs.iter().flat_map(|child| match (child.global, child.names.is_empty() && !child.is_command()) {
+      (true, _) => vec![child],
+      (false, true) => child.globals(),
+      (false, false) => Vec::new()
+    }).collect()
+  }
+
+  pub fn command_help(&self, path: &[impl AsRef<str>]) -> Option<HingeHelp> {
+    let mut current = self;
+    let mut globals = Vec::new();
+    for name in path {
+      globals.extend(current.globals());
+      current = current.commands().into_iter().find(|x| x.names.iter().any(|x| x == name.as_ref()))?;
+    }
+    let mut help = current.clone();
+    let mut taken: Vec<String> = help.collect_names().into_iter().map(String::from).collect();
+    for global in globals {
+      if global.names.iter().any(|x| taken.contains(x)) {
+        continue;
+      }
+      taken.extend(global.names.iter().cloned());
+      let mut global = global.clone();
+      global.global = false;
+      global.inherited = true;
+      global.heading.get_or_insert_with(|| String::from("Global options"));
+      help.childs.push(global);
+    }
+    let prefix = path.iter().map(|x| x.as_ref()).collect::<Vec<_>>().join(" ");
+    help.program = Some(format!("{} {}", self.program_name(), prefix).trim().to_string());
+    help.width = help.width.or(self.width);
+    help.sorted = help.sorted || self.sorted;
+    help.color = self.color;
+    help.theme = self.theme;
+    help.renderer = self.renderer.clone();
+    Some(help)
+  }
+
+  pub fn usage(&self) -> String {
+    self.usage_with(&Theme::plain())
+  }
 
-    match (header.len(), body.len()) {
-      (0, 0) => String::new(),
-      (_, 0) => header,
-      (0, _) => body,
-      (_, _) => format!("{}\n{}", header, body)
+  pub fn usage_with(&self, theme: &Theme) -> String {
+    let program = theme.get_literal().paint(self.program_name());
+    let mut parts = Vec::new();
+    let mut optional_options = false;
+    let mut command = None;
+    self.usage_parts(&mut parts, &mut optional_options, &mut command);
+    if optional_options {
+      parts.insert(0, String::from("[OPTIONS]"));
+    }
+    match command {
+      Some((true, repeated)) => parts.push(String::from("<COMMAND>") + if repeated { "..." } else { "" }),
+      Some((false, repeated)) => parts.push(String::from("[COMMAND]") + if repeated { "..." } else { "" }),
+      None => ()
+    }
+    parts.insert(0, program);
+    format!("{} {}", theme.get_heading().paint("Usage:"), parts.join(" ").trim())
+  }
+
+  pub fn generate(&self) -> String {
+    match &self.renderer {
+      Some(renderer) => self.render_with(renderer.as_ref(), false),
+      None => self.render_with(&PlainRenderer::new(), false)
     }
   }
-}
\ No newline at end of file
+
+  pub fn generate_width(&self, width: usize) -> String {
+    self.render_with(&PlainRenderer::new().width(width), false)
+  }
+
+  pub fn generate_long(&self) -> String {
+    match &self.renderer {
+      Some(renderer) => self.render_with(renderer.as_ref(), true),
+      None => self.render_with(&PlainRenderer::new(), true)
+    }
+  }
+
+  pub fn write_to(&self, renderer: &(impl HelpRenderer + ?Sized), long: bool, out: &mut impl Write) -> io::Result<()> {
+    match long {
+      true => renderer.render_long(self, out),
+      false => renderer.render(self, out)
+    }
+  }
+
+  pub fn render_with(&self, renderer: &(impl HelpRenderer + ?Sized), long: bool) -> String {
+    let mut out = Vec::new();
+    self.write_to(renderer, long, &mut out).expect("writing into memory cannot fail");
+    String::from_utf8_lossy(&out).to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::HingeBuilder;
+
+  #[test]
+  fn subcommand_help_lists_inherited_globals() {
+    let hinge = HingeBuilder::new()
+      .program("prog")
+      .bool("verbose", 'v').help("be loud")
+      .subcommand("run", "run", HingeBuilder::new().bool("dry", "dry"))
+      .require_subcommand()
+      .build();
+    let help = hinge.help_info().command_help(&["run"]).unwrap();
+    let text = help.generate();
+    assert!(text.starts_with("Usage: prog run [OPTIONS]\n"), "{}", text);
+    assert!(text.contains("Global options:\n  -v"), "{}", text);
+    assert_eq!(text.matches("--help").count(), 1, "{}", text);
+  }
+
+  #[test]
+  fn core_options_are_not_inherited() {
+    let hinge = HingeBuilder::new()
+      .program("prog")
+      .bool("verbose", 'v')
+      .subcommand("run", "run", HingeBuilder::new())
+      .build();
+    let text = hinge.help_info().command_help(&["run"]).unwrap().generate();
+    assert!(!text.contains("-v"), "{}", text);
+  }
+
+  #[test]
+  fn usage_lists_required_options_positionals_and_commands() {
+    let hinge = HingeBuilder::new()
+      .program("prog")
+      .bool("verbose", 'v')
+      .item("host", "host").require()
+      .arg("input").require()
+      .subcommand("run", "run", HingeBuilder::new())
+      .build();
+    assert_eq!(hinge.usage(), "Usage: prog [OPTIONS] --host <HOST> <input> [COMMAND]");
+    let hinge = HingeBuilder::new().program("prog").arg("a").catch_tail("rest").build();
+    assert_eq!(hinge.usage(), "Usage: prog [OPTIONS] [a] [-- <rest>...]");
+    let hinge = HingeBuilder::new().program("prog").stop_at_operand("cmd").build();
+    assert_eq!(hinge.usage(), "Usage: prog [OPTIONS] [cmd]...");
+  }
+}
@@ -0,0 +1,156 @@
+use std::io::{self, Write};
+
+use crate::{HingeHelp, HelpRenderer, HelpArity, Theme};
+
+fn string(out: &mut dyn Write, value: &str) -> io::Result<()> {
+  write!(out, "\"")?;
+  for c in value.chars() {
+    match c {
+      '"' => write!(out, "\\\"")?,
+      '\\' => write!(out, "\\\\")?,
+      '\n' => write!(out, "\\n")?,
+      '\r' => write!(out, "\\r")?,
+      '\t' => write!(out, "\\t")?,
+      c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+      c => write!(out, "{}", c)?
+    }
+  }
+  write!(out, "\"")
+}
+
+fn optional(out: &mut dyn Write, value: Option<&str>) -> io::Result<()> {
+  match value {
+    Some(value) => string(out, value),
+    None => write!(out, "null")
+  }
+}
+
+fn strings(out: &mut dyn Write, values: &[String]) -> io::Result<()> {
+  write!(out, "[")?;
+  for (index, value) in values.iter().enumerate() {
+    if index > 0 {
+      write!(out, ",")?;
+    }
+    string(out, value)?;
+  }
+  write!(out, "]")
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonRenderer;
+
+impl JsonRenderer {
+  fn entries(help: &HingeHelp) -> Vec<&HingeHelp> {
+    help.childs().iter().filter(|x| !x.is_hidden()).flat_map(|child| {
+      match child.names().is_empty() && child.alternative_name().is_none() && !child.is_positional() {
+        true => Self::entries(child),
+        false => vec![child]
+      }
+    }).collect()
+  }
+
+  fn kind(help: &HingeHelp) -> &'static str {
+    match (help.is_command(), help.is_option(), help.is_tail()) {
+      (true, _, _) => "command",
+      (_, true, _) => "option",
+      (_, _, true) => "tail",
+      _ => "argument"
+    }
+  }
+
+  fn entry(help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, "{{\"kind\":")?;
+    string(out, Self::kind(help))?;
+    write!(out, ",\"id\":")?;
+    optional(out, help.id())?;
+    write!(out, ",\"names\":")?;
+    strings(out, help.names())?;
+    write!(out, ",\"alternative_name\":")?;
+    optional(out, help.alternative_name())?;
+    write!(out, ",\"heading\":")?;
+    optional(out, help.heading())?;
+    Self::describe(help, out)?;
+    write!(out, ",\"value_name\":")?;
+    optional(out, help.value_name())?;
+    match help.arity() {
+      HelpArity::None => write!(out, ",\"arity\":\"none\",\"max_values\":null")?,
+      HelpArity::One => write!(out, ",\"arity\":\"one\",\"max_values\":1")?,
+      HelpArity::Many(Some(count)) => write!(out, ",\"arity\":\"many\",\"max_values\":{}", count)?,
+      HelpArity::Many(None) => write!(out, ",\"arity\":\"many\",\"max_values\":null")?
+    }
+    write!(out, ",\"default\":")?;
+    optional(out, help.default_value())?;
+    write!(out, ",\"env\":")?;
+    optional(out, help.env())?;
+    write!(out, ",\"possible_values\":")?;
+    strings(out, help.possible_values())?;
+    write!(out, ",\"annotations\":")?;
+    strings(out, help.annotations())?;
+    write!(out, ",\"required\":{},\"repeated\":{}", help.is_required(), help.is_repeated())?;
+    write!(out, ",\"global\":{}", help.is_global() || help.is_inherited())?;
+    Self::nested(help, out)?;
+    write!(out, "}}")
+  }
+
+  fn describe(help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, ",\"description\":")?;
+    optional(out, help.description())?;
+    write!(out, ",\"long_description\":")?;
+    optional(out, help.long_description())
+  }
+
+  fn nested(help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, ",\"entries\":[")?;
+    for (index, entry) in Self::entries(help).into_iter().enumerate() {
+      if index > 0 {
+        write!(out, ",")?;
+      }
+      Self::entry(entry, out)?;
+    }
+    write!(out, "]")
+  }
+}
+
+impl HelpRenderer for JsonRenderer {
+  fn render(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, "{{\"program\":")?;
+    optional(out, help.program())?;
+    write!(out, ",\"usage\":")?;
+    string(out, &help.usage_with(&Theme::plain()))?;
+    Self::describe(help, out)?;
+    write!(out, ",\"after_help\":")?;
+    optional(out, help.after_help())?;
+    Self::nested(help, out)?;
+    write!(out, "}}")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::HingeBuilder;
+
+  #[test]
+  fn json_lists_every_field_and_nests_subcommands() {
+    let json = HingeBuilder::new()
+      .program("prog")
+      .about("Say \"hi\"\n\tnow \\ then")
+      .bool("v", 'v').help("Be loud")
+      .bool("secret", "secret").hide()
+      .subcommand("run", "run", HingeBuilder::new().bool("dry", "dry"))
+      .build()
+      .help_json();
+    assert!(json.starts_with(concat!(
+      r#"{"program":"prog","usage":"Usage: prog [OPTIONS] [COMMAND]","#,
+      r#""description":"Say \"hi\"\n\tnow \\ then","long_description":null,"after_help":null,"entries":["#
+    )), "{}", json);
+    assert!(json.contains(concat!(
+      r#"{"kind":"option","id":"v","names":["-v"],"alternative_name":"<v>","heading":null,"#,
+      r#""description":"Be loud","long_description":null,"value_name":null,"arity":"none","max_values":null,"#,
+      r#""default":null,"env":null,"possible_values":[],"annotations":[],"required":false,"repeated":false,"#,
+      r#""global":false,"entries":[]}"#
+    )), "{}", json);
+    assert!(json.contains(r#""kind":"command","id":null,"names":["run"]"#), "{}", json);
+    assert!(json.contains(r#""entries":[{"kind":"option","id":"dry","names":["--dry"]"#), "{}", json);
+    assert!(!json.contains("secret"), "{}", json);
+  }
+}
@@ -2,10 +2,22 @@ mod consumer;
 mod output;
 mod error;
 mod help;
+mod render;
+mod json;
+mod suggestion;
+mod diagnostic;
+mod style;
 mod api;
+#[cfg(test)]
+mod testing;
 
 pub use consumer::*;
 pub use output::*;
 pub use error::*;
 pub use help::*;
+pub use render::*;
+pub use json::*;
+pub use suggestion::*;
+pub use diagnostic::*;
+pub use style::*;
 pub use api::*;
\ No newline at end of file
@@ -4,11 +4,12 @@ use crate::{error::Result, HingeError};
 
 pub type Atom = String;
 
-const OUTPUT_IS_NOT_A_VALUE: &'static str = "output is not a value";
-const INDEX_OUT_OF_BOUNDS: &'static str = "index out of bounds";
-const ITEM_DOES_NOT_EXISTS: &'static str = "item does not exists";
-const OUTPUT_IS_NOT_A_LIST: &'static str = "output is not a list";
-const OUTPUT_IS_NOT_A_MAP: &'static str = "output is not a map";
+const OUTPUT_IS_NOT_A_VALUE: &str = "output is not a value";
+const INDEX_OUT_OF_BOUNDS: &str = "index out of bounds";
+const ITEM_DOES_NOT_EXISTS: &str = "item does not exists";
+const OUTPUT_IS_NOT_A_LIST: &str = "output is not a list";
+const OUTPUT_IS_NOT_A_MAP: &str = "output is not a map";
+const OUTPUT_IS_NOT_A_COMMAND: &str = "output is not a command";
 
 #[derive(Debug, Clone)]
 pub enum HingeOutput {
@@ -17,6 +18,7 @@ pub enum HingeOutput {
   MapList(collections::HashMap<String, HingeOutput>, Vec<HingeOutput>),
   Value(Atom),
   True,
+  ShortCircuit(String),
   Empty
 }
 
@@ -36,9 +38,20 @@ impl HingeOutput {
     matches!(self, Self::Empty)
   }
 
+  pub fn is_short_circuit(&self) -> bool {
+    matches!(self, Self::ShortCircuit(_))
+  }
+
+  pub fn get_short_circuit(&self) -> Option<&str> {
+    match self {
+      Self::ShortCircuit(id) => Some(id),
+      _ => None
+    }
+  }
+
   pub fn is_value(&self, concrete: Option<impl AsRef<str>>) -> bool {
     match (self, concrete) {
-      (Self::Value(val), Some(concrete)) => *val == concrete.as_ref().to_string(),
+      (Self::Value(val), Some(concrete)) => val == concrete.as_ref(),
       (Self::Value(_), None) => true,
       _ => false
     }
@@ -65,6 +78,17 @@ impl HingeOutput {
   pub fn get_item(&self, name: impl AsRef<str>) -> Result<&HingeOutput> {
     self.get_map().and_then(|map| map.get(name.as_ref()).ok_or(ITEM_DOES_NOT_EXISTS.to_string().into()))
   }
+
+  pub fn get_command(&self) -> Result<(&str, &HingeOutput)> {
+    self.get_map().and_then(|map| match map.len() {
+      1 => map.iter().next().map(|(id, output)| (id.as_str(), output)).ok_or(OUTPUT_IS_NOT_A_COMMAND.to_string().into()),
+      _ => Err(OUTPUT_IS_NOT_A_COMMAND.to_string().into())
+    })
+  }
+
+  pub fn get_commands(&self) -> Result<Vec<(&str, &HingeOutput)>> {
+    self.get_list()?.iter().map(|x| x.get_command()).collect()
+  }
 }
 
 impl TryInto<Vec<HingeOutput>> for HingeOutput {
@@ -100,7 +124,7 @@ impl TryInto<String> for HingeOutput {
   }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct HingeCollectionBuilder {
   list: Vec<HingeOutput>,
   map: collections::HashMap<String, HingeOutput>
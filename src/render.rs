@@ -0,0 +1,590 @@
+use std::{io::{self, Write}, fmt::Debug, sync::Arc, mem};
+
+use crate::{HingeHelp, HelpArity, Theme, Stream, HingeError, HingeErrorKind, Result, display_width};
+
+const TAB: &str = "  ";
+const GAP: usize = 2;
+const ARGUMENTS: &str = "Arguments";
+const OPTIONS: &str = "Options";
+const COMMANDS: &str = "Commands";
+
+pub trait HelpRenderer: Debug + Send + Sync {
+  fn render(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()>;
+
+  fn render_long(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    self.render(help, out)
+  }
+}
+
+impl<T : HelpRenderer + ?Sized> HelpRenderer for Arc<T> {
+  fn render(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    (**self).render(help, out)
+  }
+
+  fn render_long(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    (**self).render_long(help, out)
+  }
+}
+
+#[derive(Clone, Debug)]
+struct HelpRow {
+  indent: usize,
+  key: String,
+  header: String,
+  details: String
+}
+
+type HelpSection = (String, Vec<HelpRow>);
+
+struct TidyWriter<'a> {
+  out: &'a mut dyn Write,
+  line: Vec<u8>,
+  started: bool,
+  blank: bool
+}
+
+impl<'a> TidyWriter<'a> {
+  fn new(out: &'a mut dyn Write) -> Self {
+    TidyWriter { out, line: Vec::new(), started: false, blank: false }
+  }
+
+  fn end_line(&mut self) -> io::Result<()> {
+    let line = String::from_utf8_lossy(&mem::take(&mut self.line)).to_string();
+    let line = match self.started {
+      true => line.trim_end(),
+      false => line.trim()
+    };
+    if line.is_empty() {
+      self.blank = self.started;
+      return Ok(())
+    }
+    if self.started {
+      write!(self.out, "{}", if self.blank { "\n\n" } else { "\n" })?;
+    }
+    self.started = true;
+    self.blank = false;
+    write!(self.out, "{}", line)
+  }
+
+  fn finish(mut self) -> io::Result<()> {
+    self.end_line()
+  }
+}
+
+impl Write for TidyWriter<'_> {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    for byte in buf {
+      match byte {
+        b'\n' => self.end_line()?,
+        byte => self.line.push(*byte)
+      }
+    }
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.out.flush()
+  }
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+  let mut lines = Vec::new();
+  for paragraph in text.split('\n') {
+    let mut line = String::new();
+    for word in paragraph.split_whitespace() {
+      if !line.is_empty() && display_width(&line) + 1 + display_width(word) > width {
+        lines.push(line);
+        line = String::new();
+      }
+      if !line.is_empty() {
+        line.push(' ');
+      }
+      line.push_str(word);
+    }
+    lines.push(line);
+  }
+  lines
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlainRenderer {
+  width: Option<usize>
+}
+
+impl PlainRenderer {
+  pub fn new() -> Self {
+    PlainRenderer { width: None }
+  }
+
+  pub fn width(mut self, width: usize) -> Self {
+    self.width = Some(width);
+    self
+  }
+
+  fn write(&self, help: &HingeHelp, long: bool, out: &mut dyn Write) -> io::Result<()> {
+    let theme = help.theme_for(Stream::Stdout);
+    if let Some(description) = Self::description(help, long) {
+      writeln!(out, "{}\n", description)?;
+    }
+    write!(out, "{}", help.usage_with(&theme))?;
+    let width = self.width.unwrap_or(help.width());
+    let sections = Self::sections(help, long, &theme);
+    let column = Self::column(&sections, width);
+    for section in sections.iter() {
+      write!(out, "\n\n")?;
+      Self::section(section, column, width, &theme, out)?;
+    }
+    if let Some(after_help) = help.after_help().filter(|_| long) {
+      write!(out, "\n\n{}", after_help)?;
+    }
+    Ok(())
+  }
+
+  fn sections(help: &HingeHelp, long: bool, theme: &Theme) -> Vec<HelpSection> {
+    let mut sections = Vec::new();
+    for child in help.childs() {
+      Self::collect_rows(child, None, 0, long, theme, &mut sections);
+    }
+    if let Some(position) = sections.iter().position(|(heading, _)| heading == COMMANDS) {
+      let commands = sections.remove(position);
+      sections.push(commands);
+    }
+    if help.is_sorted() {
+      for (_, rows) in sections.iter_mut() {
+        *rows = Self::sort_level(mem::take(rows));
+      }
+    }
+    sections
+  }
+
+  fn section((heading, rows): &HelpSection, column: usize, width: usize, theme: &Theme, out: &mut dyn Write) -> io::Result<()> {
+    write!(out, "{}", theme.get_heading().paint(format!("{}:", heading)))?;
+    Self::layout(rows, column, width, out)
+  }
+
+  fn sort_level(rows: Vec<HelpRow>) -> Vec<HelpRow> {
+    let level = rows.iter().map(|x| x.indent).min().unwrap_or(0);
+    let mut groups: Vec<Vec<HelpRow>> = Vec::new();
+    for row in rows {
+      match groups.last_mut() {
+        Some(group) if row.indent > level => group.push(row),
+        _ => groups.push(vec![row])
+      }
+    }
+    groups.sort_by(|a, b| a[0].key.cmp(&b[0].key));
+    groups.into_iter().flat_map(|mut group| {
+      let nested = group.split_off(1);
+      group.into_iter().chain(Self::sort_level(nested))
+    }).collect()
+  }
+
+  fn sort_key(help: &HingeHelp) -> String {
+    match help.names().first() {
+      Some(name) => name.trim_start_matches('-').to_lowercase(),
+      None => help.id().or(help.alternative_name()).unwrap_or_default().to_lowercase()
+    }
+  }
+
+  fn header(help: &HingeHelp) -> String {
+    match help.names().join(", ") {
+      names if help.is_option() && help.arity() != HelpArity::None => format!("{} {}", names, help.placeholder()),
+      names if !names.is_empty() => names,
+      _ if help.is_positional() && help.value_name().is_some() => help.placeholder(),
+      _ => help.alternative_name().unwrap_or_default().to_string()
+    }
+  }
+
+  fn description(help: &HingeHelp, long: bool) -> Option<&str> {
+    match long {
+      true => help.long_description().or(help.description()),
+      false => help.description()
+    }
+  }
+
+  fn details(help: &HingeHelp, long: bool) -> String {
+    let possible = match help.possible_values().is_empty() {
+      true => None,
+      false => Some(format!("[possible: {}]", help.possible_values().join(", ")))
+    };
+    Self::description(help, long).map(String::from).into_iter()
+      .chain(help.annotations().iter().map(|x| format!("[{}]", x)))
+      .chain(help.default_value().map(|x| format!("[default: {}]", x)))
+      .chain(help.env().map(|x| format!("[env: {}]", x)))
+      .chain(possible)
+      .chain(help.is_required().then(|| String::from("[required]")))
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+
+  fn summary(help: &HingeHelp, long: bool) -> String {
+    match help.is_command() {
+      true => Self::details(help, false).lines().next().unwrap_or_default().to_string(),
+      false => Self::details(help, long)
+    }
+  }
+
+  fn collect_rows(help: &HingeHelp, heading: Option<&str>, indent: usize, long: bool, theme: &Theme, sections: &mut Vec<HelpSection>) {
+    if help.is_hidden() {
+      return
+    }
+    let heading = help.heading().or(heading);
+    let header = Self::header(help);
+    let details = Self::summary(help, long);
+    if header.is_empty() && details.is_empty() {
+      for child in help.childs() {
+        Self::collect_rows(child, heading, indent, long, theme, sections);
+      }
+      return
+    }
+    let heading = heading.unwrap_or(match (help.is_command(), help.is_option()) {
+      (true, _) => COMMANDS,
+      (_, true) => OPTIONS,
+      _ => ARGUMENTS
+    }).to_string();
+    let position = match sections.iter().position(|x| x.0 == heading) {
+      Some(position) => position,
+      None => {
+        sections.push((heading.clone(), Vec::new()));
+        sections.len() - 1
+      }
+    };
+    sections[position].1.push(HelpRow { indent, key: Self::sort_key(help), header: theme.get_literal().paint(header), details });
+    if help.is_command() {
+      return
+    }
+    for child in help.childs() {
+      Self::collect_rows(child, Some(&heading), indent + usize::from(help.tabulate_childs()), long, theme, sections);
+    }
+  }
+
+  fn column(sections: &[HelpSection], width: usize) -> usize {
+    let limit = (width / 2).max(GAP);
+    sections.iter().flat_map(|(_, rows)| rows.iter()).map(Self::left).filter(|x| *x <= limit).max().unwrap_or(0) + GAP
+  }
+
+  fn left(row: &HelpRow) -> usize {
+    (row.indent + 1) * TAB.len() + display_width(&row.header)
+  }
+
+  fn layout(rows: &[HelpRow], column: usize, width: usize, out: &mut dyn Write) -> io::Result<()> {
+    let available = width.saturating_sub(column).max(GAP * 10);
+    for row in rows {
+      let prefix = TAB.repeat(row.indent + 1) + &row.header;
+      let details = match row.details.is_empty() {
+        true => Vec::new(),
+        false => wrap(&row.details, available)
+      };
+      let mut details = details.into_iter();
+      match details.next() {
+        Some(first) if Self::left(row) + GAP <= column => {
+          write!(out, "\n{}{}{}", prefix, " ".repeat(column - Self::left(row)), first)?;
+        },
+        Some(first) => write!(out, "\n{}\n{}{}", prefix, " ".repeat(column), first)?,
+        None => write!(out, "\n{}", prefix)?
+      }
+      for line in details {
+        match line.is_empty() {
+          true => writeln!(out)?,
+          false => write!(out, "\n{}{}", " ".repeat(column), line)?
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+impl HelpRenderer for PlainRenderer {
+  fn render(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    self.write(help, false, out)
+  }
+
+  fn render_long(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    self.write(help, true, out)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+  Text(String),
+  About,
+  Usage,
+  Options,
+  Arguments,
+  Subcommands,
+  After
+}
+
+#[derive(Clone, Debug)]
+pub struct TemplateRenderer {
+  segments: Vec<Segment>,
+  plain: PlainRenderer
+}
+
+impl TemplateRenderer {
+  pub fn new(template: impl AsRef<str>) -> Result<Self> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.as_ref().chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '{' if chars.peek() == Some(&'{') => {
+          chars.next();
+          text.push('{');
+        },
+        '}' if chars.peek() == Some(&'}') => {
+          chars.next();
+          text.push('}');
+        },
+        '{' => {
+          let mut name = String::new();
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(c) => name.push(c),
+              None => return Err(HingeError::new(HingeErrorKind::ValidationFailed, "unclosed `{` in help template"))
+            }
+          }
+          let segment = match name.as_str() {
+            "about" => Segment::About,
+            "usage" => Segment::Usage,
+            "options" => Segment::Options,
+            "arguments" => Segment::Arguments,
+            "subcommands" => Segment::Subcommands,
+            "after" => Segment::After,
+            _ => return Err(HingeError::new(
+              HingeErrorKind::ValidationFailed,
+              format!("unknown placeholder `{{{}}}` in help template", name)
+            ))
+          };
+          segments.push(Segment::Text(mem::take(&mut text)));
+          segments.push(segment);
+        },
+        '}' => return Err(HingeError::new(HingeErrorKind::ValidationFailed, "unmatched `}` in help template")),
+        c => text.push(c)
+      }
+    }
+    segments.push(Segment::Text(text));
+    Ok(TemplateRenderer { segments, plain: PlainRenderer::new() })
+  }
+
+  pub fn width(mut self, width: usize) -> Self {
+    self.plain = self.plain.width(width);
+    self
+  }
+
+  fn write(&self, help: &HingeHelp, long: bool, out: &mut dyn Write) -> io::Result<()> {
+    let theme = help.theme_for(Stream::Stdout);
+    let width = self.plain.width.unwrap_or(help.width());
+    let sections = PlainRenderer::sections(help, long, &theme);
+    let column = PlainRenderer::column(&sections, width);
+    let mut out = TidyWriter::new(out);
+    let section = |out: &mut TidyWriter, filter: &dyn Fn(&str) -> bool| -> io::Result<()> {
+      for (index, section) in sections.iter().filter(|(heading, _)| filter(heading)).enumerate() {
+        if index > 0 {
+          write!(out, "\n\n")?;
+        }
+        PlainRenderer::section(section, column, width, &theme, out)?;
+      }
+      Ok(())
+    };
+    for segment in self.segments.iter() {
+      match segment {
+        Segment::Text(text) => write!(out, "{}", text)?,
+        Segment::About => write!(out, "{}", PlainRenderer::description(help, long).unwrap_or_default())?,
+        Segment::Usage => write!(out, "{}", help.usage_with(&theme))?,
+        Segment::Options => section(&mut out, &|x| x != ARGUMENTS && x != COMMANDS)?,
+        Segment::Arguments => section(&mut out, &|x| x == ARGUMENTS)?,
+        Segment::Subcommands => section(&mut out, &|x| x == COMMANDS)?,
+        Segment::After => write!(out, "{}", help.after_help().filter(|_| long).unwrap_or_default())?
+      }
+    }
+    out.finish()
+  }
+}
+
+impl HelpRenderer for TemplateRenderer {
+  fn render(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    self.write(help, false, out)
+  }
+
+  fn render_long(&self, help: &HingeHelp, out: &mut dyn Write) -> io::Result<()> {
+    self.write(help, true, out)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{HelpRenderer, HelpRow, PlainRenderer, TemplateRenderer};
+  use crate::{ColorChoice, HingeBuilder, HingeErrorKind};
+
+  fn row(indent: usize, key: &str) -> HelpRow {
+    HelpRow { indent, key: key.to_string(), header: key.to_string(), details: String::new() }
+  }
+
+  #[test]
+  fn sorting_ignores_dashes_and_styling() {
+    let help = HingeBuilder::new()
+      .program("prog")
+      .bool("zeta", "zeta")
+      .bool("alpha", 'a')
+      .item("beta", ('b', "beta"))
+      .sort_alphabetically()
+      .color(ColorChoice::Always)
+      .build()
+      .help_info()
+      .generate();
+    let order: Vec<usize> = ["-a", "-b, --beta", "-h, --help", "--zeta"].iter().map(|x| help.find(x).unwrap()).collect();
+    assert!(order.windows(2).all(|x| x[0] < x[1]), "{}", help);
+  }
+
+  #[test]
+  fn sorting_keeps_nested_rows_under_their_parent() {
+    let rows = vec![row(0, "b"), row(1, "z"), row(1, "y"), row(0, "a"), row(1, "d"), row(1, "c")];
+    let keys: Vec<String> = PlainRenderer::sort_level(rows).into_iter().map(|x| format!("{}{}", x.indent, x.key)).collect();
+    assert_eq!(keys, vec!["0a", "1c", "1d", "0b", "1y", "1z"]);
+  }
+
+  #[test]
+  fn after_help_is_only_in_long_help() {
+    let hinge = HingeBuilder::new().program("prog").after_help("Examples: prog -v").build();
+    assert!(!hinge.help().contains("Examples"));
+    assert!(hinge.long_help().ends_with("\n\nExamples: prog -v"));
+    let hinge = HingeBuilder::new().program("prog").after_help("Examples").help_template("{usage}\n{after}").build();
+    assert_eq!(hinge.help(), "Usage: prog [OPTIONS]");
+    assert_eq!(hinge.long_help(), "Usage: prog [OPTIONS]\nExamples");
+  }
+
+  struct Chunks(Vec<String>);
+
+  impl std::io::Write for Chunks {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+      self.0.push(String::from_utf8_lossy(buf).to_string());
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn renderers_stream_rows_into_the_writer() {
+    let hinge = HingeBuilder::new().program("prog").bool("a", 'a').bool("b", 'b').bool("c", 'c').help_template("{usage}\n\n{options}").build();
+    let help = hinge.help_info();
+    for renderer in [&PlainRenderer::new() as &dyn HelpRenderer, &TemplateRenderer::new("{usage}\n\n{options}").unwrap()] {
+      let mut out = Chunks(Vec::new());
+      renderer.render(&help, &mut out).unwrap();
+      assert!(out.0.len() > 4);
+      assert_eq!(out.0.concat(), hinge.help());
+    }
+  }
+
+  #[test]
+  fn details_wrap_to_the_configured_width() {
+    let help = HingeBuilder::new()
+      .program("prog")
+      .bool("v", 'v').help("a description that is long enough to need wrapping at this width")
+      .help_width(40)
+      .build()
+      .help();
+    assert!(help.lines().all(|x| crate::display_width(x) <= 40), "{}", help);
+    assert!(help.contains("\n  -v          a description that is long\n              enough"), "{}", help);
+  }
+
+  #[test]
+  fn descriptions_align_after_the_widest_key() {
+    let help = HingeBuilder::new()
+      .program("prog")
+      .bool("v", 'v').help("Short")
+      .bool("verbose-output", "verbose-output").help("Long name")
+      .help_width(80)
+      .build()
+      .help();
+    assert!(help.ends_with([
+      "  -v                Short",
+      "  --verbose-output  Long name",
+      "  -h, --help        Print help"
+    ].join("\n").as_str()), "{}", help);
+  }
+
+  #[test]
+  fn value_names_and_annotations_follow_the_description() {
+    let help = HingeBuilder::new()
+      .program("prog")
+      .item("out", ('o', "output")).value_name("FILE")
+      .item("level", "level").default_value("3").possible_values(["1", "2", "3"])
+      .item("token", "token").env("TOKEN")
+      .item("host", "host").require().help("Server")
+      .help_width(80)
+      .build()
+      .help();
+    assert!(help.contains([
+      "  -o, --output <FILE>",
+      "  --level <LEVEL>      [default: 3] [possible: 1, 2, 3]",
+      "  --token <TOKEN>      [env: TOKEN]",
+      "  --host <HOST>        Server [required]"
+    ].join("\n").as_str()), "{}", help);
+  }
+
+  fn documented() -> crate::Hinge {
+    HingeBuilder::new()
+      .program("prog")
+      .about("Does things")
+      .bool("verbose", ('v', "verbose")).help("Print more")
+      .item("out", ('o', "output")).value_name("FILE").help("Write output to FILE")
+      .long_help("Write output to FILE.\nThe file is created when missing.")
+      .item("level", "level").default_value("3").possible_values(["1", "2", "3"])
+      .heading("Network")
+      .item("host", "host").require()
+      .help_width(60)
+      .subcommand("run", "run", HingeBuilder::new().about("Run it"))
+      .build()
+  }
+
+  #[test]
+  fn options_and_commands_are_grouped_under_headings() {
+    assert_eq!(documented().help(), [
+      "Does things",
+      "",
+      "Usage: prog [OPTIONS] --host <HOST> [COMMAND]",
+      "",
+      "Options:",
+      "  -v, --verbose        Print more",
+      "  -o, --output <FILE>  Write output to FILE",
+      "  --level <LEVEL>      [default: 3] [possible: 1, 2, 3]",
+      "  -h, --help           Print help",
+      "",
+      "Network:",
+      "  --host <HOST>        [required]",
+      "",
+      "Commands:",
+      "  run                  Run it",
+      "  help                 Print help for a subcommand"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn long_help_uses_long_descriptions() {
+    let help = documented().long_help();
+    assert!(help.contains("  -o, --output <FILE>  Write output to FILE.\n                       The file is created when missing.\n"), "{}", help);
+    assert!(!documented().help().contains("created when missing"));
+  }
+
+  #[test]
+  fn invalid_templates_fail_to_build() {
+    let error = |template: &str| match HingeBuilder::new().help_template(template).try_build() {
+      Ok(_) => panic!("accepted {:?}", template),
+      Err(error) => (error.kind(), error.to_string())
+    };
+    assert_eq!(error("{usage}\n{flags}"), (Some(HingeErrorKind::ValidationFailed), "unknown placeholder `{flags}` in help template".to_string()));
+    assert_eq!(error("{usage"), (Some(HingeErrorKind::ValidationFailed), "unclosed `{` in help template".to_string()));
+    assert_eq!(error("usage}"), (Some(HingeErrorKind::ValidationFailed), "unmatched `}` in help template".to_string()));
+    assert!(HingeBuilder::new().help_template("{{literal}} {usage}").try_build().is_ok());
+  }
+
+  #[test]
+  #[should_panic(expected = "unknown placeholder `{flags}`")]
+  fn build_panics_on_an_invalid_template() {
+    HingeBuilder::new().help_template("{flags}").build();
+  }
+}
@@ -0,0 +1,126 @@
+use std::{io::{self, IsTerminal}, str::FromStr};
+
+use crate::{HingeError, Stream};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+  #[default]
+  Auto,
+  Always,
+  Never
+}
+
+impl ColorChoice {
+  fn env_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|x| !x.is_empty() && x != "0")
+  }
+
+  pub fn enabled(&self, stream: Stream) -> bool {
+    match self {
+      Self::Always => true,
+      Self::Never => false,
+      Self::Auto if std::env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty()) => false,
+      Self::Auto if Self::env_set("CLICOLOR_FORCE") => true,
+      Self::Auto => match stream {
+        Stream::Stdout => io::stdout().is_terminal(),
+        Stream::Stderr => io::stderr().is_terminal()
+      }
+    }
+  }
+}
+
+impl FromStr for ColorChoice {
+  type Err = HingeError;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value {
+      "auto" => Ok(Self::Auto),
+      "always" => Ok(Self::Always),
+      "never" => Ok(Self::Never),
+      _ => Err(format!("invalid color choice `{}`, possible values: auto, always, never", value).into())
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style(&'static str);
+
+impl Style {
+  pub const PLAIN: Style = Style("");
+
+  pub const fn new(code: &'static str) -> Self {
+    Style(code)
+  }
+
+  pub fn paint(&self, text: impl AsRef<str>) -> String {
+    match self.0 {
+      "" => text.as_ref().to_string(),
+      code => format!("\x1b[{}m{}\x1b[0m", code, text.as_ref())
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+  heading: Style,
+  literal: Style,
+  error: Style,
+  warning: Style
+}
+
+impl Theme {
+  pub fn new() -> Self {
+    Theme {
+      heading: Style::new("1;4"),
+      literal: Style::new("1;36"),
+      error: Style::new("1;31"),
+      warning: Style::new("1;33")
+    }
+  }
+
+  pub fn plain() -> Self {
+    Theme { heading: Style::PLAIN, literal: Style::PLAIN, error: Style::PLAIN, warning: Style::PLAIN }
+  }
+
+  pub fn heading(mut self, style: Style) -> Self {
+    self.heading = style;
+    self
+  }
+
+  pub fn literal(mut self, style: Style) -> Self {
+    self.literal = style;
+    self
+  }
+
+  pub fn error(mut self, style: Style) -> Self {
+    self.error = style;
+    self
+  }
+
+  pub fn warning(mut self, style: Style) -> Self {
+    self.warning = style;
+    self
+  }
+
+  pub fn get_heading(&self) -> Style {
+    self.heading
+  }
+
+  pub fn get_literal(&self) -> Style {
+    self.literal
+  }
+
+  pub fn get_error(&self) -> Style {
+    self.error
+  }
+
+  pub fn get_warning(&self) -> Style {
+    self.warning
+  }
+}
+
+impl Default for Theme {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,30 @@
+fn distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut row: Vec<usize> = (0..=b.len()).collect();
+  for (i, ca) in a.chars().enumerate() {
+    let mut diagonal = row[0];
+    row[0] = i + 1;
+    for (j, cb) in b.iter().enumerate() {
+      let above = row[j + 1];
+      row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + usize::from(ca != *cb));
+      diagonal = above;
+    }
+  }
+  row[b.len()]
+}
+
+pub fn suggest<'a>(token: impl AsRef<str>, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+  let token = token.as_ref();
+  let mut best: Option<(usize, &str)> = None;
+  for candidate in candidates {
+    if candidate == token {
+      return None
+    }
+    let distance = distance(token, candidate);
+    let threshold = candidate.trim_start_matches('-').chars().count().div_ceil(2);
+    if distance < threshold && best.is_none_or(|(best, _)| distance < best) {
+      best = Some((distance, candidate));
+    }
+  }
+  best.map(|(_, candidate)| candidate)
+}
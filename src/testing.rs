@@ -0,0 +1,16 @@
+use crate::{Hinge, HingeOutput, Result};
+
+pub(crate) fn tokens(tokens: &[&str]) -> Vec<String> {
+  tokens.iter().map(|x| x.to_string()).collect()
+}
+
+pub(crate) fn parse(hinge: &Hinge, tokens: &[&str]) -> Result<HingeOutput> {
+  hinge.apply_tokens(self::tokens(tokens).into_iter())
+}
+
+pub(crate) fn values(output: &HingeOutput) -> Vec<&str> {
+  output.get_list().unwrap().iter().map(|x| match x {
+    HingeOutput::Value(value) => value.as_str(),
+    _ => panic!("not a value")
+  }).collect()
+}